@@ -3,12 +3,13 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     fs,
     path::{Path, PathBuf},
-    time::{SystemTime, UNIX_EPOCH},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -19,23 +20,119 @@ enum VaultError {
     Json(#[from] serde_json::Error),
     #[error("openai error: {0}")]
     OpenAi(#[from] reqwest::Error),
+    #[error("watch error: {0}")]
+    Watch(#[from] notify::Error),
     #[error("invalid data: {0}")]
     InvalidData(String),
     #[error("board not found: {0}")]
     BoardNotFound(String),
     #[error("task not found: {0}")]
     TaskNotFound(String),
-    #[error("OpenAI API key not configured. Set OPENAI_API_KEY in the environment.")]
+    #[error("project not found: {0}")]
+    ProjectNotFound(String),
+    #[error("epic not found: {0}")]
+    EpicNotFound(String),
+    #[error("column not found on board {0}: {1}")]
+    ColumnNotFound(String, String),
+    #[error("invalid id {0:?}: {1}")]
+    InvalidId(String, &'static str),
+    #[error("dependency cycle among tasks: {0}")]
+    DependencyCycle(String),
+    #[error("WIP limit reached for column {0} (limit {1})")]
+    WipLimitExceeded(String, u32),
+    #[error("git error: {0}")]
+    Git(String),
+    #[error("vault file is busy, try again: {0}")]
+    FileBusy(String),
+    #[error(
+        "OpenAI API key not configured. Set OPENAI_API_KEY in the environment or openaiApiKey in config.json."
+    )]
     OpenAiKeyMissing,
+    #[error("OpenAI request timed out. Please try again.")]
+    OpenAiTimeout,
+    #[error("OpenAI rate limit reached. Wait a moment before trying again.")]
+    OpenAiRateLimited,
+    #[error(
+        "OpenAI quota exhausted. Check the plan and billing details of the account behind the API key."
+    )]
+    OpenAiQuota,
+    #[error("could not parse OpenAI response ({reason}): {snippet}")]
+    OpenAiResponse { reason: String, snippet: String },
 }
 
 type Result<T> = std::result::Result<T, VaultError>;
 
+impl VaultError {
+    /// Stable tag the frontend can match on instead of the English message.
+    fn code(&self) -> &'static str {
+        match self {
+            VaultError::Io(_) => "io",
+            VaultError::Json(_) => "json",
+            VaultError::OpenAi(_) => "openai_request",
+            VaultError::Watch(_) => "watch",
+            VaultError::InvalidData(_) => "invalid_data",
+            VaultError::BoardNotFound(_) => "board_not_found",
+            VaultError::TaskNotFound(_) => "task_not_found",
+            VaultError::ProjectNotFound(_) => "project_not_found",
+            VaultError::EpicNotFound(_) => "epic_not_found",
+            VaultError::ColumnNotFound(_, _) => "column_not_found",
+            VaultError::InvalidId(_, _) => "invalid_id",
+            VaultError::DependencyCycle(_) => "dependency_cycle",
+            VaultError::WipLimitExceeded(_, _) => "wip_limit_exceeded",
+            VaultError::Git(_) => "git",
+            VaultError::FileBusy(_) => "file_busy",
+            VaultError::OpenAiKeyMissing => "openai_key_missing",
+            VaultError::OpenAiTimeout => "openai_timeout",
+            VaultError::OpenAiRateLimited => "openai_rate_limited",
+            VaultError::OpenAiQuota => "openai_quota",
+            VaultError::OpenAiResponse { .. } => "openai_response",
+        }
+    }
+}
+
+/// What a failed command sends across IPC.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<VaultError> for ErrorResponse {
+    fn from(e: VaultError) -> Self {
+        ErrorResponse {
+            code: e.code().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Board {
     pub id: String,
     pub title: String,
     pub columns: Vec<String>,
+    #[serde(default)]
+    pub wip_limits: BTreeMap<String, u32>,
+    /// Columns that count as finished for progress, throughput and blockers.
+    #[serde(default = "default_done_columns")]
+    pub done_columns: Vec<String>,
+}
+
+fn default_done_columns() -> Vec<String> {
+    vec![DONE_COLUMN.to_string()]
+}
+
+fn is_done_column(board: &Board, column: &str) -> bool {
+    board.done_columns.iter().any(|c| c == column)
+}
+
+// A task whose board is missing falls back to the default `Done` column.
+fn done_columns_of(db: &Db, board_id: &str) -> Vec<String> {
+    db.boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .map(|b| b.done_columns.clone())
+        .unwrap_or_else(default_done_columns)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,32 +143,200 @@ pub struct Task {
     pub column: String,
     #[serde(default)]
     pub tags: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub due: Option<String>,
     #[serde(default)]
     pub created: Option<String>,
     #[serde(default)]
     pub updated: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "projectId",
+        alias = "project_id",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub project_id: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "epicId",
+        alias = "epic_id",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub epic_id: Option<String>,
     #[serde(default)]
     pub owner: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "asA",
+        alias = "as_a",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub as_a: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "iWant",
+        alias = "i_want",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub i_want: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "soThat",
+        alias = "so_that",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub so_that: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "acceptanceCriteria",
+        alias = "acceptance_criteria",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub acceptance_criteria: Option<Vec<String>>,
     #[serde(default)]
+    pub order: Option<f64>,
+    #[serde(default)]
+    pub transitions: Vec<Transition>,
+    #[serde(default, alias = "depends_on")]
+    pub blocked_by: Vec<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_priority",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub priority: Option<Priority>,
+    /// `daily`, `weekly:<day>` or `monthly:<day of month>`; makes the task a recurring template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<String>,
+    /// On an instance: the template it was made from and the occurrence (`YYYY-MM-DD`) it covers.
+    #[serde(
+        default,
+        rename = "recurrenceOf",
+        alias = "recurrence_of",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recurrence_of: Option<String>,
+    #[serde(
+        default,
+        rename = "recurrenceDate",
+        alias = "recurrence_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recurrence_date: Option<String>,
+    /// File names under `attachments/<task id>/` in the vault.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// Card tint: `#rrggbb` or one of `TASK_COLORS`, stored lowercase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Computed from `due` when tasks are listed; never stored.
+    #[serde(
+        default,
+        rename = "dueStatus",
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_status: Option<DueStatus>,
+    /// Hand-added keys (e.g. `estimate`) kept as-is so they survive edits made in the app.
+    #[serde(flatten, deserialize_with = "custom_fields")]
+    pub custom: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
     pub body: String,
 }
 
+// `dueStatus` is computed, and would otherwise be captured here and written back twice.
+fn custom_fields<'de, D>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut custom = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    custom.remove("dueStatus");
+    Ok(custom)
+}
+
+/// `Ord` ranks `Urgent` highest and an unrecognised `Other` value lowest.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+    /// A hand-typed value (e.g. `"P1"`) kept verbatim so saving doesn't erase it.
+    #[serde(untagged)]
+    Other(serde_json::Value),
+}
+
+impl Priority {
+    fn rank(&self) -> u8 {
+        match self {
+            Priority::Other(_) => 0,
+            Priority::Low => 1,
+            Priority::Medium => 2,
+            Priority::High => 3,
+            Priority::Urgent => 4,
+        }
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank()
+            .cmp(&other.rank())
+            .then_with(|| match (self, other) {
+                (Priority::Other(a), Priority::Other(b)) => a.to_string().cmp(&b.to_string()),
+                _ => std::cmp::Ordering::Equal,
+            })
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Known names match regardless of case and spacing; anything else is kept as `Other` rather
+// than failing the whole vault.
+fn lenient_priority<'de, D>(deserializer: D) -> std::result::Result<Option<Priority>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.map(|v| {
+        let lowered = v.as_str().map(|raw| raw.trim().to_lowercase());
+        match lowered.as_deref() {
+            Some("low") => Priority::Low,
+            Some("medium") => Priority::Medium,
+            Some("high") => Priority::High,
+            Some("urgent") => Priority::Urgent,
+            _ => Priority::Other(v),
+        }
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BoardColumn {
     pub name: String,
@@ -126,6 +391,8 @@ struct Db {
     tasks: Vec<Task>,
     projects: Vec<Project>,
     epics: Vec<Epic>,
+    #[serde(default)]
+    archive: Vec<Task>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -137,6 +404,8 @@ struct OpenAiAutoFillPayload {
     i_want: Option<String>,
     so_that: Option<String>,
     acceptance_criteria: Option<Vec<String>>,
+    #[serde(default)]
+    system_prompt: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -146,23 +415,262 @@ struct OpenAiAutoFillResponse {
     as_a: Option<String>,
     i_want: Option<String>,
     so_that: Option<String>,
+    #[serde(default, deserialize_with = "loose_criteria")]
     acceptance_criteria: Option<Vec<String>>,
 }
 
-fn db_path(app: &AppHandle) -> Result<PathBuf> {
-    let base = app
-        .path()
+// Models sometimes answer with one string, or with numbers or objects inside the list.
+fn loose_criteria<'de, D>(deserializer: D) -> std::result::Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let as_text = |v: serde_json::Value| match v {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s),
+        other => Some(other.to_string()),
+    };
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            None => None,
+            Some(serde_json::Value::Array(items)) => {
+                Some(items.into_iter().filter_map(as_text).collect())
+            }
+            Some(other) => as_text(other).map(|s| vec![s]),
+        },
+    )
+}
+
+fn default_vault_dir(app: &AppHandle) -> Result<PathBuf> {
+    app.path()
         .app_data_dir()
-        .map_err(|e| VaultError::InvalidData(format!("failed to get app_data_dir: {e}")))?;
-    Ok(base.join("pm-db.json"))
+        .map_err(|e| VaultError::InvalidData(format!("failed to get app_data_dir: {e}")))
+}
+
+/// App-wide settings that live outside any single vault, so they survive `set_active_vault`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AppConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_vault: Option<String>,
+    #[serde(default)]
+    known_vaults: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    openai_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    use_slug_ids: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto_commit: Option<bool>,
+}
+
+fn app_config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(default_vault_dir(app)?.join("app-config.json"))
+}
+
+fn load_app_config(path: &Path) -> Result<AppConfig> {
+    if !path.exists() {
+        return Ok(AppConfig::default());
+    }
+    let raw = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(strip_bom(&raw))?)
+}
+
+fn save_app_config(path: &Path, config: &AppConfig) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let raw = serde_json::to_string_pretty(config)?;
+    write_atomic(path, raw.as_bytes())?;
+    restrict_to_owner(path)
+}
+
+// Config files may hold an API key, so keep them readable by the owner only.
+fn restrict_to_owner(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+// A stored vault that has since been deleted or unmounted falls back to the default one.
+fn resolve_vault_dir(default: PathBuf, config: &AppConfig) -> PathBuf {
+    config
+        .active_vault
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .unwrap_or(default)
+}
+
+/// Resolved active vault directory, loaded from `app-config.json` on first use.
+#[derive(Default)]
+struct ActiveVaultState(Mutex<Option<PathBuf>>);
+
+const VAULT_DIR_ENV: &str = "KANBAN_VAULT_DIR";
+
+fn vault_dir_override() -> Option<PathBuf> {
+    vault_dir_override_from(|key| std::env::var(key).ok())
+}
+
+fn vault_dir_override_from(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    env(VAULT_DIR_ENV)
+        .filter(|dir| !dir.trim().is_empty())
+        .map(|dir| expand_path_with(&dir, &env).unwrap_or_else(|_| PathBuf::from(dir)))
+}
+
+/// Expands a leading `~` and `$VAR` / `${VAR}` references in a user-typed path.
+fn expand_path(raw: &str) -> Result<PathBuf> {
+    expand_path_with(raw, |key| std::env::var(key).ok())
+}
+
+fn expand_path_with(raw: &str, env: impl Fn(&str) -> Option<String>) -> Result<PathBuf> {
+    if raw.contains('\0') {
+        return Err(VaultError::InvalidData(
+            "path contains a NUL byte".to_string(),
+        ));
+    }
+    let raw = raw.trim();
+    let home = || {
+        ["HOME", "USERPROFILE"]
+            .iter()
+            .find_map(|key| env(key).filter(|v| !v.is_empty()))
+            .ok_or_else(|| VaultError::InvalidData("home directory is not set".to_string()))
+    };
+    let mut expanded = String::new();
+    let mut rest = raw;
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        expanded.push_str(&home()?);
+        rest = &rest[1..];
+    }
+
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    while let Some(at) = rest.find('$') {
+        expanded.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let (name, tail) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| VaultError::InvalidData(format!("unclosed ${{ in path: {raw}")))?;
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = after.find(|c: char| !is_name(c)).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        if name.is_empty() {
+            // A lone `$` is just a character.
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let value = env(name).ok_or_else(|| {
+            VaultError::InvalidData(format!("environment variable {name} is not set"))
+        })?;
+        expanded.push_str(&value);
+        rest = tail;
+    }
+    expanded.push_str(rest);
+
+    if expanded.trim().is_empty() {
+        return Err(VaultError::InvalidData(format!(
+            "path expands to nothing: {raw:?}"
+        )));
+    }
+    Ok(PathBuf::from(expanded))
+}
+
+// KANBAN_VAULT_DIR wins over the active vault, so tests and scripts can point at a scratch dir.
+fn vault_dir(app: &AppHandle) -> Result<PathBuf> {
+    if let Some(dir) = vault_dir_override() {
+        return Ok(dir);
+    }
+    let state = app.state::<ActiveVaultState>();
+    let mut active = state
+        .0
+        .lock()
+        .map_err(|e| VaultError::InvalidData(e.to_string()))?;
+    if let Some(dir) = active.as_ref() {
+        return Ok(dir.clone());
+    }
+    let config = load_app_config(&app_config_path(app)?)?;
+    let dir = resolve_vault_dir(default_vault_dir(app)?, &config);
+    *active = Some(dir.clone());
+    Ok(dir)
+}
+
+const DB_FILE_NAME: &str = "pm-db.json";
+
+fn db_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(vault_dir(app)?.join(DB_FILE_NAME))
+}
+
+const CONFIG_FILE_NAME: &str = "config.json";
+
+fn config_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(vault_dir(app)?.join(CONFIG_FILE_NAME))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VaultConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    openai_api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_columns: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    use_slug_ids: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto_commit: Option<bool>,
+}
+
+fn load_config(path: &Path) -> Result<VaultConfig> {
+    if !path.exists() {
+        return Ok(VaultConfig::default());
+    }
+    let raw = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
+fn save_config(path: &Path, config: &VaultConfig) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let raw = serde_json::to_string_pretty(config)?;
+    write_atomic(path, raw.as_bytes())?;
+    restrict_to_owner(path)
+}
+
+// Older versions kept the key and id/commit switches in the vault's `config.json`; those
+// values still apply until the app-level ones are set.
+fn with_app_settings(mut config: VaultConfig, app_config: &AppConfig) -> VaultConfig {
+    config.openai_api_key = app_config.openai_api_key.clone().or(config.openai_api_key);
+    config.use_slug_ids = app_config.use_slug_ids.or(config.use_slug_ids);
+    config.auto_commit = app_config.auto_commit.or(config.auto_commit);
+    config
+}
+
+/// The active vault's `config.json` combined with the app-wide settings.
+fn effective_config(app: &AppHandle) -> Result<VaultConfig> {
+    let app_config = load_app_config(&app_config_path(app)?)?;
+    Ok(with_app_settings(
+        load_config(&config_path(app)?)?,
+        &app_config,
+    ))
 }
 
-fn resolve_openai_key() -> Result<String> {
+fn resolve_openai_key(config: &VaultConfig) -> Result<String> {
     if let Ok(value) = std::env::var("OPENAI_API_KEY") {
         if !value.trim().is_empty() {
             return Ok(value);
         }
     }
+    if let Some(value) = &config.openai_api_key {
+        if !value.trim().is_empty() {
+            return Ok(value.trim().to_string());
+        }
+    }
 
     Err(VaultError::OpenAiKeyMissing)
 }
@@ -174,11 +682,313 @@ fn resolve_openai_model() -> (String, String) {
     (primary, fallback)
 }
 
-fn now_epoch() -> String {
-    SystemTime::now()
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_AZURE_API_VERSION: &str = "2024-06-01";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenAiEndpoint {
+    url: String,
+    azure: bool,
+}
+
+fn openai_endpoint_from(
+    base_url: Option<String>,
+    api_type: Option<String>,
+    api_version: Option<String>,
+) -> OpenAiEndpoint {
+    let base_url = base_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_OPENAI_URL.to_string());
+    let azure = api_type
+        .map(|kind| kind.trim().eq_ignore_ascii_case("azure"))
+        .unwrap_or(false);
+    if !azure {
+        return OpenAiEndpoint {
+            url: base_url,
+            azure,
+        };
+    }
+    let version = api_version
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string());
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    OpenAiEndpoint {
+        url: format!("{base_url}{separator}api-version={version}"),
+        azure,
+    }
+}
+
+fn resolve_openai_endpoint() -> OpenAiEndpoint {
+    openai_endpoint_from(
+        std::env::var("OPENAI_BASE_URL").ok(),
+        std::env::var("OPENAI_API_TYPE").ok(),
+        std::env::var("OPENAI_API_VERSION").ok(),
+    )
+}
+
+const DEFAULT_OPENAI_TIMEOUT_SECS: u64 = 30;
+const OPENAI_MAX_RETRIES: u32 = 1;
+const OPENAI_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+const DEFAULT_OPENAI_TEMPERATURE: f64 = 0.7;
+
+fn parse_openai_temperature(raw: Option<&str>) -> f64 {
+    raw.and_then(|raw| raw.trim().parse::<f64>().ok())
+        .filter(|t| (0.0..=2.0).contains(t))
+        .unwrap_or(DEFAULT_OPENAI_TEMPERATURE)
+}
+
+fn resolve_openai_temperature() -> f64 {
+    parse_openai_temperature(std::env::var("OPENAI_TEMPERATURE").ok().as_deref())
+}
+
+fn resolve_openai_timeout() -> Duration {
+    let secs = std::env::var("OPENAI_TIMEOUT_SECS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_OPENAI_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn openai_request_error(err: reqwest::Error) -> VaultError {
+    if err.is_timeout() {
+        VaultError::OpenAiTimeout
+    } else {
+        VaultError::OpenAi(err)
+    }
+}
+
+// Out of credit is also a 429, but waiting or switching model won't help. Match the error
+// code rather than the message, which mentions billing in unrelated errors too.
+fn is_quota_error(text: &str) -> bool {
+    text.contains("insufficient_quota")
+}
+
+fn openai_status_error(status: reqwest::StatusCode, text: &str) -> VaultError {
+    if is_quota_error(text) {
+        VaultError::OpenAiQuota
+    } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        VaultError::OpenAiRateLimited
+    } else {
+        VaultError::InvalidData(format!("OpenAI error: {text}"))
+    }
+}
+
+async fn send_openai_request(
+    request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let mut attempt = 0;
+    loop {
+        let response = request().send().await.map_err(openai_request_error)?;
+        let status = response.status();
+        if !is_retryable_status(status) || attempt >= OPENAI_MAX_RETRIES {
+            return Ok(response);
+        }
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let text = response.text().await.unwrap_or_default();
+            if is_quota_error(&text) {
+                return Err(VaultError::OpenAiQuota);
+            }
+        }
+        tokio::time::sleep(OPENAI_RETRY_BASE_DELAY * 2u32.pow(attempt)).await;
+        attempt += 1;
+    }
+}
+
+fn generate_id(prefix: &str) -> String {
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs().to_string())
-        .unwrap_or_else(|_| "0".to_string())
+        .unwrap_or_default();
+    // RandomState is seeded per instance, so hashing the clock and a counter gives a cheap
+    // random suffix without pulling in a rand dependency.
+    let random =
+        RandomState::new().hash_one((now.as_nanos(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+    format!(
+        "{prefix}-{}-{:012x}",
+        now.as_millis(),
+        random & 0xffff_ffff_ffff
+    )
+}
+
+const MAX_SLUG_LEN: usize = 60;
+
+// Only ASCII letters, digits and single dashes survive, so a slug is always a safe file name.
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Ids must stay usable as file names, so refuse anything that could step out of a directory.
+fn sanitize_id(id: &str) -> Result<&str> {
+    let problem = if id.trim().is_empty() {
+        Some("id is empty")
+    } else if id.contains(['/', '\\']) {
+        Some("id contains a path separator")
+    } else if id.contains("..") {
+        Some("id contains \"..\"")
+    } else if id.starts_with('.') {
+        Some("id starts with a dot")
+    } else if id.chars().any(char::is_control) {
+        Some("id contains control characters")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(VaultError::InvalidId(id.to_string(), problem)),
+        None => Ok(id),
+    }
+}
+
+fn new_entity_id(db: &Db, prefix: &str, title: &str, slug_ids: bool) -> String {
+    let slug = slugify(title);
+    if !slug_ids || slug.is_empty() {
+        return generate_id(prefix);
+    }
+    let taken = |id: &str| {
+        db.tasks.iter().chain(&db.archive).any(|t| t.id == id)
+            || db.projects.iter().any(|p| p.id == id)
+            || db.epics.iter().any(|e| e.id == id)
+            || db.boards.iter().any(|b| b.id == id)
+    };
+    if !taken(&slug) {
+        return slug;
+    }
+    (2..)
+        .map(|n| format!("{slug}-{n}"))
+        .find(|candidate| !taken(candidate))
+        .expect("some suffix is free")
+}
+
+fn slug_ids_enabled(app: &AppHandle) -> Result<bool> {
+    Ok(effective_config(app)?.use_slug_ids.unwrap_or(false))
+}
+
+fn epoch_to_iso(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() || !trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let secs = trimmed.parse::<i64>().ok()?;
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|dt| dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn now_iso() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn normalize_timestamp(value: &mut Option<String>) -> bool {
+    match value.as_deref().and_then(epoch_to_iso) {
+        Some(iso) => {
+            *value = Some(iso);
+            true
+        }
+        None => false,
+    }
+}
+
+fn task_with_iso_timestamps(mut task: Task) -> Task {
+    normalize_timestamp(&mut task.created);
+    normalize_timestamp(&mut task.updated);
+    task
+}
+
+fn project_with_iso_timestamps(mut project: Project) -> Project {
+    normalize_timestamp(&mut project.created);
+    normalize_timestamp(&mut project.updated);
+    project
+}
+
+fn epic_with_iso_timestamps(mut epic: Epic) -> Epic {
+    normalize_timestamp(&mut epic.created);
+    normalize_timestamp(&mut epic.updated);
+    epic
+}
+
+const LOCAL_DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+fn parse_due(raw: &str) -> Option<chrono::NaiveDate> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if raw.bytes().all(|b| b.is_ascii_digit()) {
+        let secs = raw.parse::<i64>().ok()?;
+        return chrono::DateTime::from_timestamp(secs, 0).map(|dt| dt.date_naive());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.date_naive());
+    }
+    LOCAL_DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(raw, fmt).ok())
+        .map(|dt| dt.date())
+}
+
+const DUE_SOON_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", content = "days", rename_all = "camelCase")]
+pub enum DueStatus {
+    Overdue,
+    DueToday,
+    DueSoon(u32),
+    Later,
+    Unparseable,
+}
+
+fn classify_due(due: &str, today: chrono::NaiveDate) -> DueStatus {
+    let Some(date) = parse_due(due) else {
+        return DueStatus::Unparseable;
+    };
+    match (date - today).num_days() {
+        d if d < 0 => DueStatus::Overdue,
+        0 => DueStatus::DueToday,
+        d if d <= DUE_SOON_DAYS => DueStatus::DueSoon(d as u32),
+        _ => DueStatus::Later,
+    }
+}
+
+fn task_with_due_status(mut task: Task, today: chrono::NaiveDate) -> Task {
+    task.due_status = task
+        .due
+        .as_deref()
+        .filter(|due| !due.trim().is_empty())
+        .map(|due| classify_due(due, today));
+    task
+}
+
+fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
 }
 
 fn default_db() -> Db {
@@ -195,57 +1005,257 @@ fn default_db() -> Db {
                 "Review".to_string(),
                 "Done".to_string(),
             ],
+            wip_limits: BTreeMap::new(),
+            done_columns: default_done_columns(),
         }],
         tasks: vec![],
         projects: vec![],
         epics: vec![],
+        archive: vec![],
+    }
+}
+
+fn default_db_for(config: &VaultConfig) -> Db {
+    let mut db = default_db();
+    if let Some(columns) = &config.default_columns {
+        db.boards[0].columns = columns.clone();
+    }
+    db
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| VaultError::InvalidData(format!("not a file path: {}", path.display())))?;
+    // The temp file must sit next to the target so the rename stays on one filesystem.
+    let tmp = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    if let Err(rename_err) = fs::rename(&tmp, path) {
+        if !(cfg!(windows) && path.exists()) {
+            let _ = fs::remove_file(&tmp);
+            return Err(rename_err.into());
+        }
+        // Renaming over an open or read-only file can fail on Windows; retry without the target.
+        fs::remove_file(path)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                VaultError::InvalidData(format!(
+                    "failed to replace {} (rename failed: {rename_err}; fallback failed: {e}); new contents kept at {}",
+                    path.display(),
+                    tmp.display()
+                ))
+            })?;
     }
+    Ok(())
 }
 
+/// Databases already checked by `ensure_db` in this process.
+static ENSURED_DBS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
 fn ensure_db(path: &Path) -> Result<()> {
+    if ENSURED_DBS
+        .lock()
+        .map(|ensured| ensured.contains(path))
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
     if !path.exists() {
-        let db = default_db();
+        // Only a brand-new vault picks up the configured columns; existing boards are left alone.
+        let config = load_config(&path.with_file_name(CONFIG_FILE_NAME))?;
+        let db = default_db_for(&config);
         let raw = serde_json::to_string_pretty(&db)?;
-        fs::write(path, raw)?;
+        write_atomic(path, raw.as_bytes())?;
+    }
+    if let Ok(mut ensured) = ENSURED_DBS.lock() {
+        ensured.insert(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn strip_bom(raw: &str) -> &str {
+    raw.strip_prefix('\u{feff}').unwrap_or(raw)
+}
+
+// Hand-edited vaults often pick up a BOM or CRLF line endings from the editor; only the
+// BOM needs stripping, since JSON already treats CR and a missing final newline as whitespace.
+fn parse_db(raw: &str) -> Result<Db> {
+    Ok(serde_json::from_str(strip_bom(raw))?)
+}
+
+/// Last parsed copy of each database, reused while the file's mtime and size are unchanged.
+struct CachedDb {
+    modified: SystemTime,
+    len: u64,
+    db: Db,
+}
+
+static DB_CACHE: Mutex<BTreeMap<PathBuf, CachedDb>> = Mutex::new(BTreeMap::new());
+
+fn file_stamp(path: &Path) -> Result<(SystemTime, u64)> {
+    let meta = fs::metadata(path)?;
+    Ok((meta.modified()?, meta.len()))
+}
+
+fn cache_db(path: &Path, db: &Db) -> Result<()> {
+    let (modified, len) = file_stamp(path)?;
+    if let Ok(mut cache) = DB_CACHE.lock() {
+        cache.insert(
+            path.to_path_buf(),
+            CachedDb {
+                modified,
+                len,
+                db: db.clone(),
+            },
+        );
     }
     Ok(())
 }
 
+fn clear_db_cache() {
+    if let Ok(mut cache) = DB_CACHE.lock() {
+        cache.clear();
+    }
+    if let Ok(mut ensured) = ENSURED_DBS.lock() {
+        ensured.clear();
+    }
+}
+
 fn load_db(path: &Path) -> Result<Db> {
     ensure_db(path)?;
+    let (modified, len) = match file_stamp(path) {
+        // The database was deleted since it was last checked; recreate it like a fresh vault.
+        Err(VaultError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Ok(mut ensured) = ENSURED_DBS.lock() {
+                ensured.remove(path);
+            }
+            ensure_db(path)?;
+            file_stamp(path)?
+        }
+        stamp => stamp?,
+    };
+    if let Ok(cache) = DB_CACHE.lock() {
+        if let Some(cached) = cache.get(path) {
+            if cached.modified == modified && cached.len == len {
+                return Ok(cached.db.clone());
+            }
+        }
+    }
     let raw = fs::read_to_string(path)?;
-    let db: Db = serde_json::from_str(&raw)?;
+    let db = parse_db(&raw)?;
+    cache_db(path, &db)?;
     Ok(db)
 }
 
 fn save_db(path: &Path, db: &Db) -> Result<()> {
     let raw = serde_json::to_string_pretty(db)?;
-    fs::write(path, raw)?;
-    Ok(())
+    let before = fs::read_to_string(path).ok();
+    write_atomic(path, raw.as_bytes())?;
+    if let Some(before) = before {
+        journal_change(path, before, raw);
+    }
+    cache_db(path, db)
 }
 
-fn list_boards_inner(db: &Db) -> Vec<Board> {
+const DB_LOCK_ATTEMPTS: u32 = 40;
+const DB_LOCK_RETRY_DELAY: Duration = Duration::from_millis(25);
+
+// The database itself is replaced by rename on every save, so the lock lives on a sidecar
+// file that stays put. The lock is released when the returned handle is dropped.
+fn lock_db_file(path: &Path) -> Result<fs::File> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| VaultError::InvalidData(format!("not a file path: {}", path.display())))?;
+    let lock_path = path.with_file_name(format!(".{}.lock", file_name.to_string_lossy()));
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+    for _ in 0..DB_LOCK_ATTEMPTS {
+        match fs2::FileExt::try_lock_exclusive(&file) {
+            Ok(()) => {
+                UNLABELLED_CHANGE.set(None);
+                return Ok(file);
+            }
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                std::thread::sleep(DB_LOCK_RETRY_DELAY)
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(VaultError::FileBusy(path.display().to_string()))
+}
+
+/// Read-modify-write under the vault lock, so concurrent edits can't overwrite each other.
+fn with_locked_db<T>(path: &Path, mutate: impl FnOnce(&mut Db) -> Result<T>) -> Result<T> {
+    with_locked_db_if(path, |db| Ok((mutate(db)?, true)))
+}
+
+/// Like `with_locked_db`, but only saves when `mutate` reports a change, so no-op runs
+/// leave the file and the undo history alone.
+fn with_locked_db_if<T>(
+    path: &Path,
+    mutate: impl FnOnce(&mut Db) -> Result<(T, bool)>,
+) -> Result<T> {
+    let _lock = lock_db_file(path)?;
+    let mut db = load_db(path)?;
+    let (out, changed) = mutate(&mut db)?;
+    if changed {
+        save_db(path, &db)?;
+    }
+    Ok(out)
+}
+
+fn list_boards_inner(db: &Db) -> Vec<Board> {
     let mut boards = db.boards.clone();
     boards.sort_by(|a, b| a.title.cmp(&b.title));
     boards
 }
 
 fn list_tasks_inner(db: &Db, board_id: Option<&str>) -> Vec<Task> {
+    let today = today();
     let mut tasks = db
         .tasks
         .iter()
         .filter(|t| board_id.map(|b| b == t.board).unwrap_or(true))
         .cloned()
+        .map(task_with_iso_timestamps)
+        .map(|t| task_with_due_status(t, today))
         .collect::<Vec<_>>();
     tasks.sort_by(|a, b| a.title.cmp(&b.title));
     tasks
 }
 
 fn list_projects_inner(db: &Db) -> Vec<Project> {
-    let mut projects = db.projects.clone();
+    let mut projects = db
+        .projects
+        .iter()
+        .cloned()
+        .map(project_with_iso_timestamps)
+        .collect::<Vec<_>>();
     projects.sort_by(|a, b| a.title.cmp(&b.title));
     projects
 }
@@ -256,42 +1266,141 @@ fn list_epics_inner(db: &Db, project_id: Option<&str>) -> Vec<Epic> {
         .iter()
         .filter(|e| project_id.map(|p| e.project_id.as_deref() == Some(p)).unwrap_or(true))
         .cloned()
+        .map(epic_with_iso_timestamps)
         .collect::<Vec<_>>();
     epics.sort_by(|a, b| a.title.cmp(&b.title));
     epics
 }
 
-fn board_with_tasks_inner(db: &Db, board_id: &str) -> Result<BoardWithTasks> {
-    let board = db
-        .boards
-        .iter()
-        .find(|b| b.id == board_id)
-        .cloned()
-        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+fn compare_task_order(a: &Task, b: &Task) -> std::cmp::Ordering {
+    let by_order = match (a.order, b.order) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    };
+    by_order.then_with(|| a.title.cmp(&b.title))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSort {
+    Title,
+    Priority,
+    Due,
+    Created,
+    Updated,
+    #[default]
+    Order,
+}
+
+// Tasks without a priority, a readable due date or a timestamp sort after the rest;
+// timestamps sort newest first.
+fn compare_tasks_by(sort: TaskSort, a: &Task, b: &Task) -> std::cmp::Ordering {
+    use std::cmp::Reverse;
+    match sort {
+        TaskSort::Order => compare_task_order(a, b),
+        TaskSort::Title => a
+            .title
+            .to_lowercase()
+            .cmp(&b.title.to_lowercase())
+            .then_with(|| compare_task_order(a, b)),
+        TaskSort::Priority => (a.priority.is_none(), Reverse(&a.priority))
+            .cmp(&(b.priority.is_none(), Reverse(&b.priority)))
+            .then_with(|| compare_task_order(a, b)),
+        TaskSort::Due => {
+            let due = |t: &Task| t.due.as_deref().and_then(parse_due);
+            let (x, y) = (due(a), due(b));
+            (x.is_none(), x)
+                .cmp(&(y.is_none(), y))
+                .then_with(|| compare_task_order(a, b))
+        }
+        TaskSort::Created | TaskSort::Updated => {
+            let at = |t: &Task| {
+                let raw = if sort == TaskSort::Created {
+                    &t.created
+                } else {
+                    &t.updated
+                };
+                raw.as_deref().and_then(parse_instant)
+            };
+            let (x, y) = (at(a), at(b));
+            (x.is_none(), Reverse(x))
+                .cmp(&(y.is_none(), Reverse(y)))
+                .then_with(|| compare_task_order(a, b))
+        }
+    }
+}
+
+fn set_task_column(task: &mut Task, column: &str, now: &str) {
+    if task.column != column {
+        task.transitions.push(Transition {
+            from: task.column.clone(),
+            to: column.to_string(),
+            at: now.to_string(),
+        });
+        task.column = column.to_string();
+    }
+    task.updated = Some(now.to_string());
+}
 
+fn bucket_into_columns<'a>(
+    board: &Board,
+    tasks: impl Iterator<Item = &'a Task>,
+    sort: TaskSort,
+) -> Vec<BoardColumn> {
+    let today = today();
     let mut by_col: HashMap<String, Vec<Task>> = HashMap::new();
-    for t in db.tasks.iter().filter(|t| t.board == board_id) {
-        by_col.entry(t.column.clone()).or_default().push(t.clone());
+    for t in tasks {
+        by_col
+            .entry(t.column.clone())
+            .or_default()
+            .push(task_with_due_status(
+                task_with_iso_timestamps(t.clone()),
+                today,
+            ));
     }
 
-    let columns = board
+    board
         .columns
         .iter()
         .map(|name| {
             let mut tasks = by_col.remove(name).unwrap_or_default();
-            tasks.sort_by(|a, b| a.title.cmp(&b.title));
+            tasks.sort_by(|a, b| compare_tasks_by(sort, a, b));
             BoardColumn {
                 name: name.clone(),
                 tasks,
             }
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+fn board_with_tasks_inner(db: &Db, board_id: &str) -> Result<BoardWithTasks> {
+    board_with_sorted_tasks_inner(db, board_id, TaskSort::Order)
+}
 
+fn board_with_sorted_tasks_inner(
+    db: &Db,
+    board_id: &str,
+    sort: TaskSort,
+) -> Result<BoardWithTasks> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .cloned()
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+
+    let columns = bucket_into_columns(
+        &board,
+        db.tasks.iter().filter(|t| t.board == board_id),
+        sort,
+    );
     Ok(BoardWithTasks { board, columns })
 }
 
 #[tauri::command]
-fn vault_info(app: AppHandle) -> std::result::Result<VaultInfo, String> {
+fn vault_info(app: AppHandle) -> std::result::Result<VaultInfo, ErrorResponse> {
     (|| -> Result<VaultInfo> {
         let path = db_path(&app)?;
         ensure_db(&path)?;
@@ -299,40 +1408,81 @@ fn vault_info(app: AppHandle) -> std::result::Result<VaultInfo, String> {
             path: path.to_string_lossy().to_string(),
         })
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[tauri::command]
-fn list_boards(app: AppHandle) -> std::result::Result<Vec<Board>, String> {
+fn list_boards(app: AppHandle) -> std::result::Result<Vec<Board>, ErrorResponse> {
     (|| -> Result<Vec<Board>> {
         let path = db_path(&app)?;
         let db = load_db(&path)?;
         Ok(list_boards_inner(&db))
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPage {
+    pub items: Vec<Task>,
+    /// Matching tasks before `offset`/`limit` were applied.
+    pub total: usize,
+}
+
+fn list_tasks_page_inner(
+    db: &Db,
+    board_id: Option<&str>,
+    sort: Option<TaskSort>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> TaskPage {
+    let mut tasks = list_tasks_inner(db, board_id);
+    if let Some(sort) = sort {
+        tasks.sort_by(|a, b| compare_tasks_by(sort, a, b));
+    }
+    let total = tasks.len();
+    let items = tasks
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    TaskPage { items, total }
 }
 
 #[tauri::command]
-fn list_tasks(app: AppHandle, board_id: Option<String>) -> std::result::Result<Vec<Task>, String> {
-    (|| -> Result<Vec<Task>> {
+fn list_tasks(
+    app: AppHandle,
+    board_id: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort: Option<TaskSort>,
+) -> std::result::Result<TaskPage, ErrorResponse> {
+    (|| -> Result<TaskPage> {
         let path = db_path(&app)?;
         let db = load_db(&path)?;
-        Ok(list_tasks_inner(&db, board_id.as_deref()))
+        Ok(list_tasks_page_inner(
+            &db,
+            board_id.as_deref(),
+            sort,
+            offset,
+            limit,
+        ))
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[tauri::command]
 fn get_board_with_tasks(
     app: AppHandle,
     board_id: String,
-) -> std::result::Result<BoardWithTasks, String> {
+    sort: Option<TaskSort>,
+) -> std::result::Result<BoardWithTasks, ErrorResponse> {
     (|| -> Result<BoardWithTasks> {
         let path = db_path(&app)?;
         let db = load_db(&path)?;
-        board_with_tasks_inner(&db, &board_id)
+        board_with_sorted_tasks_inner(&db, &board_id, sort.unwrap_or_default())
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -340,53 +1490,105 @@ fn get_board_with_tasks(
 pub struct UpdateTaskColumnPayload {
     pub task_id: String,
     pub column: String,
+    pub force: Option<bool>,
+}
+
+fn check_wip_limit(db: &Db, board_id: &str, column: &str, task_id: &str) -> Result<()> {
+    let board = board_with_tasks_inner(db, board_id)?;
+    let Some(&limit) = board.board.wip_limits.get(column) else {
+        return Ok(());
+    };
+    let occupied = board
+        .columns
+        .iter()
+        .find(|c| c.name == column)
+        .map(|c| c.tasks.iter().filter(|t| t.id != task_id).count())
+        .unwrap_or(0);
+    if occupied >= limit as usize {
+        return Err(VaultError::WipLimitExceeded(column.to_string(), limit));
+    }
+    Ok(())
+}
+
+fn update_task_column_inner(db: &mut Db, task_id: &str, column: &str, force: bool) -> Result<Task> {
+    let board_id = db
+        .tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .map(|t| t.board.clone())
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.clone()))?;
+    if !board.columns.iter().any(|c| c == column) {
+        return Err(VaultError::ColumnNotFound(board_id, column.to_string()));
+    }
+    if !force {
+        check_wip_limit(db, &board_id, column, task_id)?;
+    }
+
+    let task = db
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    set_task_column(task, column, &now_iso());
+    Ok(task_with_iso_timestamps(task.clone()))
+}
+
+fn update_task_column_at(path: &Path, payload: &UpdateTaskColumnPayload) -> Result<Task> {
+    with_locked_db(path, |db| {
+        update_task_column_inner(
+            db,
+            &payload.task_id,
+            &payload.column,
+            payload.force.unwrap_or(false),
+        )
+    })
 }
 
 #[tauri::command]
 fn update_task_column(
     app: AppHandle,
     payload: UpdateTaskColumnPayload,
-) -> std::result::Result<Task, String> {
+) -> std::result::Result<Task, ErrorResponse> {
     (|| -> Result<Task> {
-        let path = db_path(&app)?;
-        let mut db = load_db(&path)?;
-
-        let updated = {
-            let task = db
-                .tasks
-                .iter_mut()
-                .find(|t| t.id == payload.task_id)
-                .ok_or_else(|| VaultError::TaskNotFound(payload.task_id.clone()))?;
-
-            task.column = payload.column.clone();
-            task.updated = Some(now_epoch());
-            task.clone()
-        };
-
-        save_db(&path, &db)?;
+        let updated = update_task_column_at(&db_path(&app)?, &payload)?;
+        log_activity(
+            &app,
+            "move",
+            "task",
+            &updated.id,
+            json!({ "column": updated.column }),
+        );
         Ok(updated)
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[tauri::command]
-fn list_projects(app: AppHandle) -> std::result::Result<Vec<Project>, String> {
+fn list_projects(app: AppHandle) -> std::result::Result<Vec<Project>, ErrorResponse> {
     (|| -> Result<Vec<Project>> {
         let path = db_path(&app)?;
         let db = load_db(&path)?;
         Ok(list_projects_inner(&db))
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[tauri::command]
-fn list_epics(app: AppHandle, project_id: Option<String>) -> std::result::Result<Vec<Epic>, String> {
+fn list_epics(
+    app: AppHandle,
+    project_id: Option<String>,
+) -> std::result::Result<Vec<Epic>, ErrorResponse> {
     (|| -> Result<Vec<Epic>> {
         let path = db_path(&app)?;
         let db = load_db(&path)?;
         Ok(list_epics_inner(&db, project_id.as_deref()))
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -406,7 +1608,7 @@ pub struct CreateEpicPayload {
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateStoryPayload {
     pub title: String,
@@ -419,220 +1621,9464 @@ pub struct CreateStoryPayload {
     pub so_that: Option<String>,
     pub acceptance_criteria: Option<Vec<String>>,
     pub column: Option<String>,
+    pub board_id: Option<String>,
 }
 
 #[tauri::command]
 fn create_project(
     app: AppHandle,
     payload: CreateProjectPayload,
-) -> std::result::Result<Project, String> {
+) -> std::result::Result<Project, ErrorResponse> {
     (|| -> Result<Project> {
-        let path = db_path(&app)?;
-        let mut db = load_db(&path)?;
-
-        let id = format!("project-{}", now_epoch());
-        let fm = Project {
-            id: id.clone(),
-            title: payload.title,
-            owner: payload.owner,
-            created: Some(now_epoch()),
-            updated: None,
-            description: payload.description.clone(),
-        };
-
-        db.projects.push(fm.clone());
-        save_db(&path, &db)?;
+        let slug_ids = slug_ids_enabled(&app)?;
+        let fm = with_locked_db(&db_path(&app)?, |db| {
+            let fm = Project {
+                id: new_entity_id(db, "project", &payload.title, slug_ids),
+                title: payload.title,
+                owner: payload.owner,
+                created: Some(now_iso()),
+                updated: None,
+                description: payload.description.clone(),
+            };
+            db.projects.push(fm.clone());
+            Ok(fm)
+        })?;
+        log_activity(
+            &app,
+            "create",
+            "project",
+            &fm.id,
+            json!({ "title": fm.title }),
+        );
         Ok(fm)
     })()
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
 #[tauri::command]
-fn create_epic(app: AppHandle, payload: CreateEpicPayload) -> std::result::Result<Epic, String> {
+fn create_epic(
+    app: AppHandle,
+    payload: CreateEpicPayload,
+) -> std::result::Result<Epic, ErrorResponse> {
     (|| -> Result<Epic> {
-        let path = db_path(&app)?;
-        let mut db = load_db(&path)?;
+        let slug_ids = slug_ids_enabled(&app)?;
+        let fm = with_locked_db(&db_path(&app)?, |db| {
+            let fm = Epic {
+                id: new_entity_id(db, "epic", &payload.title, slug_ids),
+                title: payload.title,
+                project_id: payload.project_id,
+                owner: payload.owner,
+                created: Some(now_iso()),
+                updated: None,
+                description: payload.description.clone(),
+            };
+            db.epics.push(fm.clone());
+            Ok(fm)
+        })?;
+        log_activity(
+            &app,
+            "create",
+            "epic",
+            &fm.id,
+            json!({ "title": fm.title, "projectId": fm.project_id }),
+        );
+        Ok(fm)
+    })()
+    .map_err(ErrorResponse::from)
+}
 
-        let id = format!("epic-{}", now_epoch());
-        let fm = Epic {
-            id: id.clone(),
-            title: payload.title,
-            project_id: payload.project_id,
-            owner: payload.owner,
-            created: Some(now_epoch()),
-            updated: None,
-            description: payload.description.clone(),
+fn create_story_inner(db: &mut Db, payload: CreateStoryPayload, slug_ids: bool) -> Result<Task> {
+    let board_id = payload.board_id.unwrap_or_else(|| "default".to_string());
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.clone()))?;
+    // Vaults pick their own columns, so without an explicit one the story starts in the
+    // board's first column rather than a "Backlog" that may not exist.
+    let column =
+        match payload.column {
+            Some(column) => column,
+            None => board.columns.first().cloned().ok_or_else(|| {
+                VaultError::InvalidData(format!("board {board_id} has no columns"))
+            })?,
         };
+    if !board.columns.contains(&column) {
+        return Err(VaultError::ColumnNotFound(board_id, column));
+    }
 
-        db.epics.push(fm.clone());
-        save_db(&path, &db)?;
-        Ok(fm)
-    })()
-    .map_err(|e| e.to_string())
+    let id = new_entity_id(db, "story", &payload.title, slug_ids);
+    let description = payload.description.clone().unwrap_or_default();
+    let fm = Task {
+        id: id.clone(),
+        title: payload.title,
+        board: board_id,
+        column,
+        tags: vec!["story".to_string()],
+        due: None,
+        created: Some(now_iso()),
+        updated: None,
+        project_id: payload.project_id,
+        epic_id: payload.epic_id,
+        owner: payload.owner,
+        description: if description.is_empty() {
+            None
+        } else {
+            Some(description.clone())
+        },
+        as_a: payload.as_a,
+        i_want: payload.i_want,
+        so_that: payload.so_that,
+        acceptance_criteria: payload.acceptance_criteria,
+        order: None,
+        transitions: vec![],
+        blocked_by: vec![],
+        checklist: vec![],
+        archived: None,
+        priority: None,
+        recurrence: None,
+        recurrence_of: None,
+        recurrence_date: None,
+        attachments: vec![],
+        color: None,
+        due_status: None,
+        custom: BTreeMap::new(),
+        body: description,
+    };
+
+    db.tasks.push(fm.clone());
+    Ok(fm)
 }
 
 #[tauri::command]
-fn create_story(app: AppHandle, payload: CreateStoryPayload) -> std::result::Result<Task, String> {
+fn create_story(
+    app: AppHandle,
+    payload: CreateStoryPayload,
+) -> std::result::Result<Task, ErrorResponse> {
     (|| -> Result<Task> {
-        let path = db_path(&app)?;
-        let mut db = load_db(&path)?;
+        let slug_ids = slug_ids_enabled(&app)?;
+        let created = with_locked_db(&db_path(&app)?, |db| {
+            create_story_inner(db, payload, slug_ids)
+        })?;
+        log_activity(
+            &app,
+            "create",
+            "task",
+            &created.id,
+            json!({ "title": created.title, "board": created.board, "column": created.column }),
+        );
+        Ok(created)
+    })()
+    .map_err(ErrorResponse::from)
+}
 
-        let id = format!("story-{}", now_epoch());
-        let description = payload.description.clone().unwrap_or_default();
-        let fm = Task {
-            id: id.clone(),
-            title: payload.title,
-            board: "default".to_string(),
-            column: payload
-                .column
-                .unwrap_or_else(|| "Backlog".to_string()),
-            tags: vec!["story".to_string()],
-            due: None,
-            created: Some(now_epoch()),
-            updated: None,
-            project_id: payload.project_id,
-            epic_id: payload.epic_id,
-            owner: payload.owner,
-            description: if description.is_empty() {
-                None
-            } else {
-                Some(description.clone())
-            },
-            as_a: payload.as_a,
-            i_want: payload.i_want,
-            so_that: payload.so_that,
-            acceptance_criteria: payload.acceptance_criteria,
-            body: description,
-        };
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCounts {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
 
-        db.tasks.push(fm.clone());
-        save_db(&path, &db)?;
-        Ok(fm)
-    })()
-    .map_err(|e| e.to_string())
+impl TokenCounts {
+    fn add(&mut self, other: &TokenCounts) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiUsage {
+    pub total: TokenCounts,
+    pub by_model: BTreeMap<String, TokenCounts>,
+}
+
+#[derive(Default)]
+struct OpenAiUsageState(Mutex<OpenAiUsage>);
+
+fn record_openai_usage(totals: &mut OpenAiUsage, response: &serde_json::Value, model: &str) {
+    let Some(usage) = response.get("usage") else {
+        return;
+    };
+    let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let counts = TokenCounts {
+        requests: 1,
+        prompt_tokens: count("prompt_tokens"),
+        completion_tokens: count("completion_tokens"),
+        total_tokens: count("total_tokens"),
+    };
+    // Prefer the model the API reports, since a fallback may have answered the request.
+    let model = response
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or(model);
+    totals.total.add(&counts);
+    totals
+        .by_model
+        .entry(model.to_string())
+        .or_default()
+        .add(&counts);
 }
 
 #[tauri::command]
-async fn openai_autofill_story(
-    _app: AppHandle,
-    payload: OpenAiAutoFillPayload,
-) -> std::result::Result<OpenAiAutoFillResponse, String> {
-    (async move {
-        let api_key = resolve_openai_key()?;
-        let (model, fallback_model) = resolve_openai_model();
-        let prompt = format!(
-            "Generate missing story fields. Return JSON only with keys: title, asA, iWant, soThat, acceptanceCriteria (array of strings).\n\nDescription: {}\nExisting title: {}\nExisting asA: {}\nExisting iWant: {}\nExisting soThat: {}\nExisting acceptanceCriteria: {}",
-            payload.description,
-            payload.title.clone().unwrap_or_default(),
-            payload.as_a.clone().unwrap_or_default(),
-            payload.i_want.clone().unwrap_or_default(),
-            payload.so_that.clone().unwrap_or_default(),
-            payload
-                .acceptance_criteria
-                .clone()
-                .unwrap_or_default()
-                .join("; ")
-        );
-
-        let client = reqwest::Client::new();
-
-        let request = |model_name: &str| {
-            let body = json!({
-                "model": model_name,
-                "messages": [
-                    {
-                        "role": "system",
-                        "content": "You are a product manager writing user stories. Only return JSON, no markdown. Keep answers concise. Use null for fields you cannot infer."
-                    },
-                    { "role": "user", "content": prompt }
-                ],
-                "response_format": { "type": "json_object" }
-            });
+fn openai_usage(
+    usage: tauri::State<'_, OpenAiUsageState>,
+) -> std::result::Result<OpenAiUsage, ErrorResponse> {
+    usage
+        .0
+        .lock()
+        .map(|totals| totals.clone())
+        .map_err(|e| VaultError::InvalidData(e.to_string()).into())
+}
 
-            client
-                .post("https://api.openai.com/v1/chat/completions")
-                .bearer_auth(&api_key)
-                .json(&body)
-        };
+#[tauri::command]
+fn reset_openai_usage(
+    usage: tauri::State<'_, OpenAiUsageState>,
+) -> std::result::Result<(), ErrorResponse> {
+    let mut totals = usage
+        .0
+        .lock()
+        .map_err(|e| VaultError::InvalidData(e.to_string()))?;
+    *totals = OpenAiUsage::default();
+    Ok(())
+}
 
-        let mut response = request(&model).send().await?;
+const AUTOFILL_KEYS: [&str; 5] = ["title", "asA", "iWant", "soThat", "acceptanceCriteria"];
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            let should_fallback = model != fallback_model
-                && (status.as_u16() == 404 || text.to_lowercase().contains("model"));
+fn response_snippet(content: &str) -> String {
+    const MAX_CHARS: usize = 200;
+    let trimmed = content.trim();
+    if trimmed.chars().count() <= MAX_CHARS {
+        return trimmed.to_string();
+    }
+    let mut snippet = trimmed.chars().take(MAX_CHARS).collect::<String>();
+    snippet.push('…');
+    snippet
+}
 
-            if should_fallback {
-                response = request(&fallback_model).send().await?;
-            } else {
-                return Err(VaultError::InvalidData(format!(
-                    "OpenAI error: {text}"
-                )));
+fn strip_code_fences(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    // Drop the info string (e.g. `json`) on the opening fence line.
+    let rest = rest.split_once('\n').map(|(_, body)| body).unwrap_or("");
+    rest.trim_end().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+fn extract_json_object(content: &str) -> Option<&str> {
+    let start = content.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in content[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[start..=start + offset]);
+                }
             }
+            _ => {}
         }
+    }
+    None
+}
 
-        if !response.status().is_success() {
-            let text = response.text().await.unwrap_or_default();
-            return Err(VaultError::InvalidData(format!(
-                "OpenAI error: {text}"
-            )));
+fn parse_autofill_content(content: &str) -> Result<OpenAiAutoFillResponse> {
+    let malformed = |reason: String| VaultError::OpenAiResponse {
+        reason,
+        snippet: response_snippet(content),
+    };
+
+    let object = extract_json_object(strip_code_fences(content))
+        .ok_or_else(|| malformed("no JSON object found".to_string()))?;
+    let value: serde_json::Value =
+        serde_json::from_str(object).map_err(|e| malformed(e.to_string()))?;
+    let map = value
+        .as_object()
+        .ok_or_else(|| malformed("expected a JSON object".to_string()))?;
+    let missing = AUTOFILL_KEYS
+        .iter()
+        .filter(|key| !map.contains_key(**key))
+        .copied()
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(malformed(format!("missing keys: {}", missing.join(", "))));
+    }
+    serde_json::from_value(value).map_err(|e| malformed(e.to_string()))
+}
+
+const AUTOFILL_SYSTEM_PROMPT: &str = "You are a product manager writing user stories. Only return JSON, no markdown. Keep answers concise. Use null for fields you cannot infer.";
+
+fn autofill_prompt(payload: &OpenAiAutoFillPayload) -> String {
+    format!(
+        "Generate missing story fields. Return JSON only with keys: title, asA, iWant, soThat, acceptanceCriteria (array of strings).\n\nDescription: {}\nExisting title: {}\nExisting asA: {}\nExisting iWant: {}\nExisting soThat: {}\nExisting acceptanceCriteria: {}",
+        payload.description,
+        payload.title.clone().unwrap_or_default(),
+        payload.as_a.clone().unwrap_or_default(),
+        payload.i_want.clone().unwrap_or_default(),
+        payload.so_that.clone().unwrap_or_default(),
+        payload
+            .acceptance_criteria
+            .clone()
+            .unwrap_or_default()
+            .join("; ")
+    )
+}
+
+async fn request_autofill(
+    app: &AppHandle,
+    payload: &OpenAiAutoFillPayload,
+    stream: bool,
+) -> Result<(reqwest::Response, String)> {
+    let prompt = autofill_prompt(payload);
+    let system_prompt = payload
+        .system_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(AUTOFILL_SYSTEM_PROMPT);
+    request_openai_json(app, system_prompt, &prompt, stream).await
+}
+
+/// Sends a JSON-mode chat completion (falling back to the fallback model when the primary
+/// one is unavailable) and returns the successful response with the model that produced it.
+async fn request_openai_json(
+    app: &AppHandle,
+    system_prompt: &str,
+    prompt: &str,
+    stream: bool,
+) -> Result<(reqwest::Response, String)> {
+    let api_key = resolve_openai_key(&effective_config(app)?)?;
+    let (model, fallback_model) = resolve_openai_model();
+    let endpoint = resolve_openai_endpoint();
+    let temperature = resolve_openai_temperature();
+
+    // A streamed completion can legitimately run longer than the timeout, so only bound
+    // the gaps between chunks.
+    let client = if stream {
+        reqwest::Client::builder().read_timeout(resolve_openai_timeout())
+    } else {
+        reqwest::Client::builder().timeout(resolve_openai_timeout())
+    }
+    .build()?;
+
+    let request = |model_name: &str| {
+        let mut body = json!({
+            "model": model_name,
+            "messages": [
+                { "role": "system", "content": system_prompt },
+                { "role": "user", "content": prompt }
+            ],
+            "temperature": temperature,
+            "response_format": { "type": "json_object" }
+        });
+        if stream {
+            body["stream"] = json!(true);
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+
+        let builder = client.post(&endpoint.url).json(&body);
+        if endpoint.azure {
+            builder.header("api-key", &api_key)
+        } else {
+            builder.bearer_auth(&api_key)
+        }
+    };
+
+    let mut response = send_openai_request(|| request(&model)).await?;
+    let mut used_model = model.clone();
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let should_fallback = model != fallback_model
+            && !is_quota_error(&text)
+            && (status.as_u16() == 404 || text.to_lowercase().contains("model"));
+
+        if should_fallback {
+            response = send_openai_request(|| request(&fallback_model)).await?;
+            used_model = fallback_model.clone();
+        } else {
+            return Err(openai_status_error(status, &text));
         }
+    }
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(openai_status_error(status, &text));
+    }
 
-        let value: serde_json::Value = response.json().await?;
-        let content = value
-            .get("choices")
-            .and_then(|choices| choices.get(0))
-            .and_then(|choice| choice.get("message"))
-            .and_then(|message| message.get("content"))
-            .and_then(|content| content.as_str())
-            .unwrap_or("{}");
+    Ok((response, used_model))
+}
 
-        let parsed: OpenAiAutoFillResponse = serde_json::from_str(content)?;
-        Ok(parsed)
+#[tauri::command]
+async fn openai_autofill_story(
+    app: AppHandle,
+    usage: tauri::State<'_, OpenAiUsageState>,
+    payload: OpenAiAutoFillPayload,
+) -> std::result::Result<OpenAiAutoFillResponse, ErrorResponse> {
+    (async move {
+        if openai_mock_enabled() {
+            return parse_autofill_content(&mock_autofill_content(&payload));
+        }
+        let (response, model) = request_autofill(&app, &payload, false).await?;
+        let value: serde_json::Value = response.json().await.map_err(openai_request_error)?;
+        if let Ok(mut totals) = usage.0.lock() {
+            record_openai_usage(&mut totals, &value, &model);
+        }
+        parse_autofill_content(completion_content(&value))
     })
     .await
-    .map_err(|e| e.to_string())
+    .map_err(ErrorResponse::from)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn completion_content(value: &serde_json::Value) -> &str {
+    value
+        .get("choices")
+        .and_then(|choices| choices.get(0))
+        .and_then(|choice| choice.get("message"))
+        .and_then(|message| message.get("content"))
+        .and_then(|content| content.as_str())
+        .unwrap_or("{}")
+}
 
-    fn temp_path() -> PathBuf {
-        let mut path = std::env::temp_dir();
-        path.push(format!("pm-db-test-{}.json", now_epoch()));
-        path
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DriftSource {
+    Description,
+    Body,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DescriptionBodyDrift {
+    pub task_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub body: String,
+}
+
+fn description_body_drift_inner(
+    db: &mut Db,
+    reconcile: Option<DriftSource>,
+) -> Vec<DescriptionBodyDrift> {
+    let mut drifted = Vec::new();
+    for task in db.tasks.iter_mut() {
+        let Some(description) = task.description.as_deref() else {
+            continue;
+        };
+        if description.trim() == task.body.trim() {
+            continue;
+        }
+
+        drifted.push(DescriptionBodyDrift {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            body: task.body.clone(),
+        });
+
+        match reconcile {
+            Some(DriftSource::Description) => {
+                task.body = description.to_string();
+            }
+            Some(DriftSource::Body) => {
+                task.description = if task.body.trim().is_empty() {
+                    None
+                } else {
+                    Some(task.body.clone())
+                };
+            }
+            None => continue,
+        }
+        task.updated = Some(now_iso());
     }
+    drifted.sort_by(|a, b| a.title.cmp(&b.title));
+    drifted
+}
 
-    #[test]
-    fn creates_default_db() {
-        let path = temp_path();
-        ensure_db(&path).unwrap();
-        let db = load_db(&path).unwrap();
-        assert_eq!(db.boards.len(), 1);
-        assert!(db.tasks.is_empty());
-        let _ = fs::remove_file(path);
+#[tauri::command]
+fn find_description_body_drift(
+    app: AppHandle,
+    reconcile: Option<DriftSource>,
+) -> std::result::Result<Vec<DescriptionBodyDrift>, ErrorResponse> {
+    (|| -> Result<Vec<DescriptionBodyDrift>> {
+        let drifted = with_locked_db_if(&db_path(&app)?, |db| {
+            let drifted = description_body_drift_inner(db, reconcile);
+            let changed = reconcile.is_some() && !drifted.is_empty();
+            Ok((drifted, changed))
+        })?;
+        if reconcile.is_some() && !drifted.is_empty() {
+            log_activity(
+                &app,
+                "reconcile_description",
+                "vault",
+                "",
+                json!({ "tasks": drifted.len() }),
+            );
+        }
+        Ok(drifted)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameColumnPayload {
+    pub board_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+fn rename_column_inner(db: &mut Db, board_id: &str, from: &str, to: &str) -> Result<Vec<String>> {
+    let from = from.trim();
+    let to = to.trim();
+    if to.is_empty() {
+        return Err(VaultError::InvalidData(
+            "column name cannot be empty".to_string(),
+        ));
+    }
+
+    let board = db
+        .boards
+        .iter_mut()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+    let index = board
+        .columns
+        .iter()
+        .position(|c| c == from)
+        .ok_or_else(|| VaultError::ColumnNotFound(board_id.to_string(), from.to_string()))?;
+    if from != to && board.columns.iter().any(|c| c == to) {
+        return Err(VaultError::InvalidData(format!(
+            "column already exists on board {board_id}: {to}"
+        )));
     }
+    board.columns[index] = to.to_string();
+    for done in board.done_columns.iter_mut().filter(|c| *c == from) {
+        *done = to.to_string();
+    }
+    if let Some(limit) = board.wip_limits.remove(from) {
+        board.wip_limits.insert(to.to_string(), limit);
+    }
+
+    let now = now_iso();
+    let mut migrated = Vec::new();
+    for task in db
+        .tasks
+        .iter_mut()
+        .filter(|t| t.board == board_id && t.column == from)
+    {
+        task.column = to.to_string();
+        task.updated = Some(now.clone());
+        migrated.push(task.id.clone());
+    }
+    Ok(migrated)
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![
-            vault_info,
-            list_boards,
-            list_tasks,
-            get_board_with_tasks,
-            update_task_column,
-            list_projects,
+#[tauri::command]
+fn rename_column(
+    app: AppHandle,
+    payload: RenameColumnPayload,
+) -> std::result::Result<BoardWithTasks, ErrorResponse> {
+    (|| -> Result<BoardWithTasks> {
+        let path = db_path(&app)?;
+        // Locked by hand rather than via `with_locked_db` to report the pending tasks on a failed save.
+        let _lock = lock_db_file(&path)?;
+        let mut db = load_db(&path)?;
+
+        let migrated = rename_column_inner(&mut db, &payload.board_id, &payload.from, &payload.to)?;
+        // The board and its cards live in the same file, so a failed save leaves
+        // every task on the old column; report which ones were pending.
+        save_db(&path, &db).map_err(|e| {
+            VaultError::InvalidData(format!(
+                "failed to save column rename, tasks not migrated ({}): {e}",
+                migrated.join(", ")
+            ))
+        })?;
+        log_activity(
+            &app,
+            "rename_column",
+            "board",
+            &payload.board_id,
+            json!({ "from": payload.from, "to": payload.to, "tasks": migrated.len() }),
+        );
+        board_with_tasks_inner(&db, &payload.board_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn escape_html(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const SAFE_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+// Browsers drop whitespace and control characters inside a scheme, so they are ignored here too.
+fn is_safe_url(url: &str) -> bool {
+    let cleaned = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>();
+    match cleaned.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => SAFE_URL_SCHEMES
+            .iter()
+            .any(|safe| scheme.eq_ignore_ascii_case(safe)),
+        // No scheme: a relative path or fragment.
+        _ => true,
+    }
+}
+
+fn render_markdown_html(markdown: &str) -> String {
+    use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+    // Raw HTML in a card body is shown as text so the export never carries markup or scripts
+    // from the vault; link and image targets are limited to `SAFE_URL_SCHEMES` and relative paths.
+    let events = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS).map(
+        |event| match event {
+            Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) if !is_safe_url(&dest_url) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: CowStr::Borrowed(""),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) if !is_safe_url(&dest_url) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: CowStr::Borrowed(""),
+                title,
+                id,
+            }),
+            other => other,
+        },
+    );
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+const BOARD_HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,sans-serif;margin:24px;color:#1d1d1f;background:#f5f5f7}\
+.board{display:flex;gap:16px;align-items:flex-start;overflow-x:auto}\
+.column{flex:0 0 260px;background:#e8e8ed;border-radius:8px;padding:12px}\
+.column h2{font-size:15px;margin:0 0 8px}\
+.count{color:#6e6e73;font-weight:normal}\
+.card{background:#fff;border-radius:6px;padding:10px;margin-bottom:8px;box-shadow:0 1px 2px rgba(0,0,0,.08)}\
+.card h3{font-size:14px;margin:0 0 6px}\
+.meta{font-size:12px;color:#6e6e73}\
+.tag{display:inline-block;background:#e8e8ed;border-radius:4px;padding:0 6px;margin-right:4px}\
+.body{font-size:13px}";
+
+fn render_board_html(board: &BoardWithTasks) -> String {
+    let title = escape_html(&board.board.title);
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{title}</title>\n"));
+    out.push_str(&format!("<style>{BOARD_HTML_STYLE}</style>\n"));
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{title}</h1>\n<div class=\"board\">\n"));
+
+    for column in &board.columns {
+        out.push_str(&format!(
+            "<section class=\"column\">\n<h2>{} <span class=\"count\">{}</span></h2>\n",
+            escape_html(&column.name),
+            column.tasks.len()
+        ));
+        for task in &column.tasks {
+            out.push_str("<article class=\"card\">\n");
+            out.push_str(&format!("<h3>{}</h3>\n", escape_html(&task.title)));
+            if !task.tags.is_empty() || task.due.is_some() {
+                out.push_str("<div class=\"meta\">");
+                for tag in &task.tags {
+                    out.push_str(&format!("<span class=\"tag\">{}</span>", escape_html(tag)));
+                }
+                if let Some(due) = &task.due {
+                    out.push_str(&format!(
+                        "<span class=\"due\">Due {}</span>",
+                        escape_html(due)
+                    ));
+                }
+                out.push_str("</div>\n");
+            }
+            if !task.body.trim().is_empty() {
+                out.push_str(&format!(
+                    "<div class=\"body\">{}</div>\n",
+                    render_markdown_html(&task.body)
+                ));
+            }
+            out.push_str("</article>\n");
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</div>\n</body>\n</html>\n");
+    out
+}
+
+#[tauri::command]
+fn export_board_html(
+    app: AppHandle,
+    board_id: String,
+    dest_path: String,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let board = board_with_tasks_inner(&db, &board_id)?;
+        fs::write(&dest_path, render_board_html(&board))?;
+        Ok(dest_path)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderTasksPayload {
+    pub board_id: String,
+    pub column: String,
+    pub task_ids: Vec<String>,
+}
+
+fn reorder_tasks_inner(
+    db: &mut Db,
+    board_id: &str,
+    column: &str,
+    task_ids: &[String],
+) -> Result<Vec<Task>> {
+    let mut seen = std::collections::HashSet::new();
+    for id in task_ids {
+        if !seen.insert(id) {
+            return Err(VaultError::InvalidData(format!(
+                "task {id} is listed twice"
+            )));
+        }
+        let task = db
+            .tasks
+            .iter()
+            .find(|t| &t.id == id)
+            .ok_or_else(|| VaultError::TaskNotFound(id.clone()))?;
+        if task.board != board_id {
+            return Err(VaultError::InvalidData(format!(
+                "task {id} is on board {}, not {board_id}",
+                task.board
+            )));
+        }
+        if task.column != column {
+            return Err(VaultError::InvalidData(format!(
+                "task {id} is in column {}, not {column}",
+                task.column
+            )));
+        }
+    }
+
+    let now = now_iso();
+    let mut reordered = Vec::with_capacity(task_ids.len());
+    for (index, id) in task_ids.iter().enumerate() {
+        if let Some(task) = db.tasks.iter_mut().find(|t| &t.id == id) {
+            task.order = Some(index as f64);
+            task.updated = Some(now.clone());
+            reordered.push(task_with_iso_timestamps(task.clone()));
+        }
+    }
+    Ok(reordered)
+}
+
+#[tauri::command]
+fn reorder_tasks(
+    app: AppHandle,
+    payload: ReorderTasksPayload,
+) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let reordered = with_locked_db(&db_path(&app)?, |db| {
+            reorder_tasks_inner(db, &payload.board_id, &payload.column, &payload.task_ids)
+        })?;
+        log_activity(
+            &app,
+            "reorder",
+            "column",
+            &payload.column,
+            json!({ "boardId": payload.board_id, "taskIds": payload.task_ids }),
+        );
+        Ok(reordered)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+/// Counts every task, project and epic that had at least one timestamp rewritten.
+fn migrate_timestamps_to_iso_inner(db: &mut Db) -> usize {
+    let mut migrated = 0;
+    for task in db.tasks.iter_mut().chain(db.archive.iter_mut()) {
+        let created = normalize_timestamp(&mut task.created);
+        let updated = normalize_timestamp(&mut task.updated);
+        let mut moved = false;
+        for transition in task.transitions.iter_mut() {
+            if let Some(iso) = epoch_to_iso(&transition.at) {
+                transition.at = iso;
+                moved = true;
+            }
+        }
+        if created || updated || moved {
+            migrated += 1;
+        }
+    }
+    for project in db.projects.iter_mut() {
+        let created = normalize_timestamp(&mut project.created);
+        let updated = normalize_timestamp(&mut project.updated);
+        if created || updated {
+            migrated += 1;
+        }
+    }
+    for epic in db.epics.iter_mut() {
+        let created = normalize_timestamp(&mut epic.created);
+        let updated = normalize_timestamp(&mut epic.updated);
+        if created || updated {
+            migrated += 1;
+        }
+    }
+    migrated
+}
+
+#[tauri::command]
+fn migrate_timestamps_to_iso(app: AppHandle) -> std::result::Result<usize, ErrorResponse> {
+    (|| -> Result<usize> {
+        let migrated = with_locked_db_if(&db_path(&app)?, |db| {
+            let migrated = migrate_timestamps_to_iso_inner(db);
+            Ok((migrated, migrated > 0))
+        })?;
+        if migrated > 0 {
+            log_activity(
+                &app,
+                "migrate_timestamps",
+                "vault",
+                "",
+                json!({ "entities": migrated }),
+            );
+        }
+        Ok(migrated)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const DONE_COLUMN: &str = "Done";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DueBucketKind {
+    Overdue,
+    Today,
+    ThisWeek,
+    Later,
+    NoDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DueBucket {
+    pub bucket: DueBucketKind,
+    pub tasks: Vec<Task>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DueBuckets {
+    pub buckets: Vec<DueBucket>,
+    pub excluded: Vec<Task>,
+}
+
+fn due_bucket_kind(due: Option<&str>, today: chrono::NaiveDate) -> DueBucketKind {
+    let Some(date) = due.and_then(parse_due) else {
+        return DueBucketKind::NoDate;
+    };
+    match (date - today).num_days() {
+        d if d < 0 => DueBucketKind::Overdue,
+        0 => DueBucketKind::Today,
+        1..=6 => DueBucketKind::ThisWeek,
+        _ => DueBucketKind::Later,
+    }
+}
+
+fn tasks_by_due_bucket_inner(
+    db: &Db,
+    board_id: Option<&str>,
+    include_done: bool,
+    today: chrono::NaiveDate,
+) -> DueBuckets {
+    let kinds = [
+        DueBucketKind::Overdue,
+        DueBucketKind::Today,
+        DueBucketKind::ThisWeek,
+        DueBucketKind::Later,
+        DueBucketKind::NoDate,
+    ];
+    let mut buckets = kinds
+        .iter()
+        .map(|&bucket| DueBucket {
+            bucket,
+            tasks: vec![],
+        })
+        .collect::<Vec<_>>();
+    let mut excluded = Vec::new();
+
+    for task in list_tasks_inner(db, board_id) {
+        if !include_done && done_columns_of(db, &task.board).contains(&task.column) {
+            excluded.push(task);
+            continue;
+        }
+        let kind = due_bucket_kind(task.due.as_deref(), today);
+        if let Some(bucket) = buckets.iter_mut().find(|b| b.bucket == kind) {
+            bucket.tasks.push(task);
+        }
+    }
+
+    for bucket in buckets.iter_mut() {
+        bucket.tasks.sort_by(|a, b| {
+            let a_due = a.due.as_deref().and_then(parse_due);
+            let b_due = b.due.as_deref().and_then(parse_due);
+            a_due.cmp(&b_due).then_with(|| a.title.cmp(&b.title))
+        });
+    }
+
+    DueBuckets { buckets, excluded }
+}
+
+#[tauri::command]
+fn tasks_by_due_bucket(
+    app: AppHandle,
+    board_id: Option<String>,
+    include_done: Option<bool>,
+) -> std::result::Result<DueBuckets, ErrorResponse> {
+    (|| -> Result<DueBuckets> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(tasks_by_due_bucket_inner(
+            &db,
+            board_id.as_deref(),
+            include_done.unwrap_or(false),
+            today(),
+        ))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveTaskToBoardPayload {
+    pub task_id: String,
+    pub target_board_id: String,
+    pub column: Option<String>,
+    pub force: Option<bool>,
+}
+
+fn move_task_to_board_inner(
+    db: &mut Db,
+    task_id: &str,
+    target_board_id: &str,
+    column: Option<&str>,
+    force: bool,
+) -> Result<Task> {
+    let board = list_boards_inner(db)
+        .into_iter()
+        .find(|b| b.id == target_board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(target_board_id.to_string()))?;
+    let column = match column {
+        Some(column) => column.to_string(),
+        None => board.columns.first().cloned().ok_or_else(|| {
+            VaultError::InvalidData(format!("board {target_board_id} has no columns"))
+        })?,
+    };
+    if !board.columns.contains(&column) {
+        return Err(VaultError::ColumnNotFound(
+            target_board_id.to_string(),
+            column,
+        ));
+    }
+    if !force {
+        check_wip_limit(db, target_board_id, &column, task_id)?;
+    }
+
+    let task = db
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    task.board = board.id;
+    set_task_column(task, &column, &now_iso());
+    Ok(task_with_iso_timestamps(task.clone()))
+}
+
+#[tauri::command]
+fn move_task_to_board(
+    app: AppHandle,
+    payload: MoveTaskToBoardPayload,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let moved = with_locked_db(&db_path(&app)?, |db| {
+            move_task_to_board_inner(
+                db,
+                &payload.task_id,
+                &payload.target_board_id,
+                payload.column.as_deref(),
+                payload.force.unwrap_or(false),
+            )
+        })?;
+        log_activity(
+            &app,
+            "move_to_board",
+            "task",
+            &moved.id,
+            json!({ "board": moved.board, "column": moved.column }),
+        );
+        Ok(moved)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeBoardsPayload {
+    pub source_board_id: String,
+    pub target_board_id: String,
+    #[serde(default)]
+    pub column_map: HashMap<String, String>,
+}
+
+fn merge_boards_inner(
+    db: &mut Db,
+    source_board_id: &str,
+    target_board_id: &str,
+    column_map: &HashMap<String, String>,
+) -> Result<usize> {
+    if source_board_id == target_board_id {
+        return Err(VaultError::InvalidData(
+            "cannot merge a board into itself".to_string(),
+        ));
+    }
+    if !db.boards.iter().any(|b| b.id == source_board_id) {
+        return Err(VaultError::BoardNotFound(source_board_id.to_string()));
+    }
+    let target = db
+        .boards
+        .iter()
+        .find(|b| b.id == target_board_id)
+        .cloned()
+        .ok_or_else(|| VaultError::BoardNotFound(target_board_id.to_string()))?;
+    for to in column_map.values() {
+        if !target.columns.contains(to) {
+            return Err(VaultError::ColumnNotFound(
+                target_board_id.to_string(),
+                to.clone(),
+            ));
+        }
+    }
+    let fallback = target.columns.first().cloned().ok_or_else(|| {
+        VaultError::InvalidData(format!("board {target_board_id} has no columns"))
+    })?;
+
+    let now = now_iso();
+    let mut moved = 0;
+    for task in db.tasks.iter_mut().filter(|t| t.board == source_board_id) {
+        let column = column_map.get(&task.column).unwrap_or(&fallback);
+        set_task_column(task, column, &now);
+        task.board = target_board_id.to_string();
+        moved += 1;
+    }
+    db.boards.retain(|b| b.id != source_board_id);
+    Ok(moved)
+}
+
+#[tauri::command]
+fn merge_boards(
+    app: AppHandle,
+    payload: MergeBoardsPayload,
+) -> std::result::Result<BoardWithTasks, ErrorResponse> {
+    (|| -> Result<BoardWithTasks> {
+        let merged = with_locked_db(&db_path(&app)?, |db| {
+            merge_boards_inner(
+                db,
+                &payload.source_board_id,
+                &payload.target_board_id,
+                &payload.column_map,
+            )?;
+            board_with_tasks_inner(db, &payload.target_board_id)
+        })?;
+        log_activity(
+            &app,
+            "merge",
+            "board",
+            &payload.source_board_id,
+            json!({ "into": payload.target_board_id }),
+        );
+        Ok(merged)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+fn search_tasks_inner(db: &Db, query: &str, board_id: Option<&str>, limit: usize) -> Vec<Task> {
+    let terms = query
+        .split_whitespace()
+        .map(|t| t.to_lowercase())
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let mut ranked = list_tasks_inner(db, board_id)
+        .into_iter()
+        .filter_map(|task| {
+            let title = task.title.to_lowercase();
+            let body = task.body.to_lowercase();
+            if !terms
+                .iter()
+                .all(|t| title.contains(t.as_str()) || body.contains(t.as_str()))
+            {
+                return None;
+            }
+            let in_title = terms.iter().filter(|t| title.contains(t.as_str())).count();
+            let rank = if in_title == terms.len() {
+                0
+            } else if in_title > 0 {
+                1
+            } else {
+                2
+            };
+            Some((rank, task))
+        })
+        .collect::<Vec<_>>();
+    // list_tasks_inner is already title-sorted and sort_by_key is stable.
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().take(limit).map(|(_, t)| t).collect()
+}
+
+#[tauri::command]
+fn search_tasks(
+    app: AppHandle,
+    query: String,
+    board_id: Option<String>,
+    limit: Option<usize>,
+) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(search_tasks_inner(
+            &db,
+            &query,
+            board_id.as_deref(),
+            limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        ))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnPreview {
+    pub name: String,
+    pub count: usize,
+    pub first_title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BoardPreview {
+    pub id: String,
+    pub title: String,
+    pub columns: Vec<ColumnPreview>,
+}
+
+fn board_previews_inner(db: &Db) -> Vec<BoardPreview> {
+    let mut by_col: HashMap<(&str, &str), (usize, &Task)> = HashMap::new();
+    for task in &db.tasks {
+        by_col
+            .entry((task.board.as_str(), task.column.as_str()))
+            .and_modify(|(count, first)| {
+                *count += 1;
+                if compare_task_order(task, first).is_lt() {
+                    *first = task;
+                }
+            })
+            .or_insert((1, task));
+    }
+
+    list_boards_inner(db)
+        .into_iter()
+        .map(|board| {
+            let columns = board
+                .columns
+                .iter()
+                .map(|name| {
+                    let entry = by_col.get(&(board.id.as_str(), name.as_str()));
+                    ColumnPreview {
+                        name: name.clone(),
+                        count: entry.map(|(count, _)| *count).unwrap_or(0),
+                        first_title: entry.map(|(_, first)| first.title.clone()),
+                    }
+                })
+                .collect();
+            BoardPreview {
+                id: board.id,
+                title: board.title,
+                columns,
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn board_previews(app: AppHandle) -> std::result::Result<Vec<BoardPreview>, ErrorResponse> {
+    (|| -> Result<Vec<BoardPreview>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(board_previews_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn flatten_board_inner(db: &mut Db, board_id: &str, into_column: &str) -> Result<usize> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+    if !board.columns.iter().any(|c| c == into_column) {
+        return Err(VaultError::ColumnNotFound(
+            board_id.to_string(),
+            into_column.to_string(),
+        ));
+    }
+
+    let now = now_iso();
+    let mut moved = 0;
+    for task in db
+        .tasks
+        .iter_mut()
+        .filter(|t| t.board == board_id && t.column != into_column)
+    {
+        set_task_column(task, into_column, &now);
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+#[tauri::command]
+fn flatten_board(
+    app: AppHandle,
+    board_id: String,
+    into_column: String,
+) -> std::result::Result<usize, ErrorResponse> {
+    (|| -> Result<usize> {
+        let moved = with_locked_db_if(&db_path(&app)?, |db| {
+            let moved = flatten_board_inner(db, &board_id, &into_column)?;
+            Ok((moved, moved > 0))
+        })?;
+        if moved > 0 {
+            log_activity(
+                &app,
+                "flatten",
+                "board",
+                &board_id,
+                json!({ "column": into_column, "tasks": moved }),
+            );
+        }
+        Ok(moved)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const VAULT_CHANGED_EVENT: &str = "vault://changed";
+const VAULT_WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VaultChanged {
+    pub path: String,
+}
+
+fn is_vault_change(event: &notify::Result<notify::Event>, db_file: &Path) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, notify::EventKind::Access(_))
+                && event
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name() == db_file.file_name())
+        }
+        Err(_) => false,
+    }
+}
+
+/// The watcher for the active vault; replacing it stops the previous one.
+#[derive(Default)]
+struct VaultWatcherState(Mutex<Option<notify::RecommendedWatcher>>);
+
+fn start_vault_watcher(app: AppHandle) -> Result<()> {
+    use notify::Watcher;
+    use std::sync::mpsc;
+
+    let state = app.state::<VaultWatcherState>();
+    let mut current = state
+        .0
+        .lock()
+        .map_err(|e| VaultError::InvalidData(e.to_string()))?;
+    // Stop watching the previous vault even if the new one can't be watched.
+    current.take();
+
+    let path = db_path(&app)?;
+    ensure_db(&path)?;
+    let dir = path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| VaultError::InvalidData("database path has no parent".to_string()))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Editors often save via rename, so watch the directory rather than the file itself.
+    watcher.watch(&dir, notify::RecursiveMode::NonRecursive)?;
+
+    // Dropping the watcher closes the channel, which ends this thread.
+    let emitter = app.clone();
+    std::thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let mut changed = is_vault_change(&first, &path);
+            let deadline = std::time::Instant::now() + VAULT_WATCH_DEBOUNCE;
+            loop {
+                let wait = deadline.saturating_duration_since(std::time::Instant::now());
+                match rx.recv_timeout(wait) {
+                    Ok(event) => changed |= is_vault_change(&event, &path),
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if changed {
+                let _ = emitter.emit(
+                    VAULT_CHANGED_EVENT,
+                    VaultChanged {
+                        path: path.to_string_lossy().to_string(),
+                    },
+                );
+            }
+        }
+    });
+    *current = Some(watcher);
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultIssue {
+    pub path: String,
+    pub error: String,
+}
+
+struct EntryFailure {
+    index: usize,
+    id: Option<String>,
+    error: String,
+}
+
+/// Deserializes each entry of `collection` on its own, so one bad record doesn't hide the rest.
+fn parse_entries<T: serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+    collection: &str,
+) -> (Vec<T>, Vec<EntryFailure>) {
+    let entries = value
+        .get(collection)
+        .and_then(|entries| entries.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut parsed = Vec::new();
+    let mut failures = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        match T::deserialize(entry) {
+            Ok(item) => parsed.push(item),
+            Err(e) => failures.push(EntryFailure {
+                index,
+                id: entry
+                    .get("id")
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string),
+                error: e.to_string(),
+            }),
+        }
+    }
+    (parsed, failures)
+}
+
+fn collection_issues<T: serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+    collection: &str,
+) -> Vec<VaultIssue> {
+    match value.get(collection) {
+        None => return Vec::new(),
+        Some(entries) if !entries.is_array() => {
+            return vec![VaultIssue {
+                path: collection.to_string(),
+                error: "expected an array".to_string(),
+            }]
+        }
+        Some(_) => {}
+    }
+    parse_entries::<T>(value, collection)
+        .1
+        .into_iter()
+        .map(|failure| VaultIssue {
+            path: match failure.id {
+                Some(id) => format!("{collection}[{}] ({id})", failure.index),
+                None => format!("{collection}[{}]", failure.index),
+            },
+            error: failure.error,
+        })
+        .collect()
+}
+
+fn vault_diagnostics_inner(raw: &str) -> Vec<VaultIssue> {
+    let value: serde_json::Value = match serde_json::from_str(strip_bom(raw)) {
+        Ok(value) => value,
+        Err(e) => {
+            return vec![VaultIssue {
+                path: "$".to_string(),
+                error: e.to_string(),
+            }]
+        }
+    };
+    let mut issues = Vec::new();
+    issues.extend(collection_issues::<Board>(&value, "boards"));
+    issues.extend(collection_issues::<Task>(&value, "tasks"));
+    issues.extend(collection_issues::<Project>(&value, "projects"));
+    issues.extend(collection_issues::<Epic>(&value, "epics"));
+    issues.extend(collection_issues::<Task>(&value, "archive"));
+    // Entries can all be valid while the document as a whole is not (e.g. a missing collection).
+    if issues.is_empty() {
+        if let Err(e) = serde_json::from_value::<Db>(value) {
+            issues.push(VaultIssue {
+                path: "$".to_string(),
+                error: e.to_string(),
+            });
+        }
+    }
+    issues
+}
+
+#[tauri::command]
+fn vault_diagnostics(app: AppHandle) -> std::result::Result<Vec<VaultIssue>, ErrorResponse> {
+    (|| -> Result<Vec<VaultIssue>> {
+        let path = db_path(&app)?;
+        ensure_db(&path)?;
+        let raw = fs::read_to_string(&path)?;
+        Ok(vault_diagnostics_inner(&raw))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn topological_task_order_inner(db: &Db, board_id: &str) -> Result<Vec<Task>> {
+    if !db.boards.iter().any(|b| b.id == board_id) {
+        return Err(VaultError::BoardNotFound(board_id.to_string()));
+    }
+    let tasks = db
+        .tasks
+        .iter()
+        .filter(|t| t.board == board_id)
+        .collect::<Vec<_>>();
+    let index = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    // Blockers on other boards (or that no longer exist) don't constrain this board's order.
+    let mut pending = vec![0usize; tasks.len()];
+    let mut unblocks = vec![Vec::new(); tasks.len()];
+    for (i, task) in tasks.iter().enumerate() {
+        for blocker in &task.blocked_by {
+            if let Some(&b) = index.get(blocker.as_str()) {
+                pending[i] += 1;
+                unblocks[b].push(i);
+            }
+        }
+    }
+
+    let mut ready = (0..tasks.len())
+        .filter(|&i| pending[i] == 0)
+        .collect::<Vec<_>>();
+    let mut ordered = Vec::with_capacity(tasks.len());
+    while !ready.is_empty() {
+        let (pos, _) = ready
+            .iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| compare_task_order(tasks[a], tasks[b]))
+            .expect("ready is not empty");
+        let next = ready.swap_remove(pos);
+        ordered.push(next);
+        for &blocked in &unblocks[next] {
+            pending[blocked] -= 1;
+            if pending[blocked] == 0 {
+                ready.push(blocked);
+            }
+        }
+    }
+
+    if ordered.len() < tasks.len() {
+        let mut stuck = (0..tasks.len())
+            .filter(|&i| pending[i] > 0)
+            .map(|i| tasks[i].id.clone())
+            .collect::<Vec<_>>();
+        stuck.sort();
+        return Err(VaultError::DependencyCycle(stuck.join(", ")));
+    }
+
+    Ok(ordered
+        .into_iter()
+        .map(|i| task_with_iso_timestamps(tasks[i].clone()))
+        .collect())
+}
+
+#[tauri::command]
+fn topological_task_order(
+    app: AppHandle,
+    board_id: String,
+) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        topological_task_order_inner(&db, &board_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Default)]
+struct FrontmatterCheckpoints {
+    next: u64,
+    saved: HashMap<String, Task>,
+}
+
+#[derive(Default)]
+struct FrontmatterCheckpointState(Mutex<FrontmatterCheckpoints>);
+
+fn save_task_frontmatter_checkpoint_inner(
+    db: &Db,
+    checkpoints: &mut FrontmatterCheckpoints,
+    task_id: &str,
+) -> Result<String> {
+    let task = db
+        .tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    checkpoints.next += 1;
+    let token = format!("{}#{}", task.id, checkpoints.next);
+    checkpoints.saved.insert(token.clone(), task.clone());
+    Ok(token)
+}
+
+// A column renamed or removed since the task was set aside falls back to the board's first column.
+fn restorable_column(db: &Db, board_id: &str, column: &str) -> Result<String> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+    if board.columns.iter().any(|c| c == column) {
+        return Ok(column.to_string());
+    }
+    board
+        .columns
+        .first()
+        .cloned()
+        .ok_or_else(|| VaultError::InvalidData(format!("board {board_id} has no columns")))
+}
+
+fn restore_task_frontmatter_inner(
+    db: &mut Db,
+    checkpoints: &FrontmatterCheckpoints,
+    token: &str,
+) -> Result<Task> {
+    let saved = checkpoints
+        .saved
+        .get(token)
+        .ok_or_else(|| VaultError::InvalidData(format!("unknown checkpoint: {token}")))?;
+    let column = restorable_column(db, &saved.board, &saved.column)?;
+    let task = db
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == saved.id)
+        .ok_or_else(|| VaultError::TaskNotFound(saved.id.clone()))?;
+    let body = std::mem::take(&mut task.body);
+    let attachments = std::mem::take(&mut task.attachments);
+    *task = Task {
+        body,
+        attachments,
+        column,
+        updated: Some(now_iso()),
+        ..saved.clone()
+    };
+    Ok(task_with_iso_timestamps(task.clone()))
+}
+
+#[tauri::command]
+fn save_task_frontmatter_checkpoint(
+    app: AppHandle,
+    checkpoints: tauri::State<'_, FrontmatterCheckpointState>,
+    task_id: String,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let mut checkpoints = checkpoints
+            .0
+            .lock()
+            .map_err(|e| VaultError::InvalidData(e.to_string()))?;
+        save_task_frontmatter_checkpoint_inner(&db, &mut checkpoints, &task_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn restore_task_frontmatter(
+    app: AppHandle,
+    checkpoints: tauri::State<'_, FrontmatterCheckpointState>,
+    token: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let checkpoints = checkpoints
+            .0
+            .lock()
+            .map_err(|e| VaultError::InvalidData(e.to_string()))?;
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            restore_task_frontmatter_inner(db, &checkpoints, &token)
+        })?;
+        log_activity(&app, "restore", "task", &task.id, json!({ "token": token }));
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn set_openai_key(app: AppHandle, key: String) -> std::result::Result<(), ErrorResponse> {
+    (|| -> Result<()> {
+        let path = app_config_path(&app)?;
+        let mut config = load_app_config(&path)?;
+        let key = key.trim();
+        config.openai_api_key = (!key.is_empty()).then(|| key.to_string());
+        save_app_config(&path, &config)?;
+        // Drop a key left in the vault's config by older versions so clearing it sticks.
+        let vault_path = config_path(&app)?;
+        let mut vault_config = load_config(&vault_path)?;
+        if vault_config.openai_api_key.take().is_some() {
+            save_config(&vault_path, &vault_config)?;
+        }
+        Ok(())
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JourneyStep {
+    pub column: String,
+    pub entered_at: Option<String>,
+}
+
+fn task_column_journey_inner(task: &Task) -> Vec<JourneyStep> {
+    let created = task
+        .created
+        .as_deref()
+        .map(|raw| epoch_to_iso(raw).unwrap_or_else(|| raw.to_string()));
+    let first_column = task
+        .transitions
+        .first()
+        .map(|t| t.from.clone())
+        .unwrap_or_else(|| task.column.clone());
+    let mut journey = vec![JourneyStep {
+        column: first_column,
+        entered_at: created,
+    }];
+    journey.extend(task.transitions.iter().map(|t| JourneyStep {
+        column: t.to.clone(),
+        entered_at: Some(t.at.clone()),
+    }));
+    // Columns edited outside the app leave no transition; still end the journey where the task is.
+    if journey
+        .last()
+        .map(|step| step.column != task.column)
+        .unwrap_or(false)
+    {
+        journey.push(JourneyStep {
+            column: task.column.clone(),
+            entered_at: None,
+        });
+    }
+    journey
+}
+
+#[tauri::command]
+fn task_column_journey(
+    app: AppHandle,
+    task_id: String,
+) -> std::result::Result<Vec<JourneyStep>, ErrorResponse> {
+    (|| -> Result<Vec<JourneyStep>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let task = db
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| VaultError::TaskNotFound(task_id.clone()))?;
+        Ok(task_column_journey_inner(task))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn get_task_inner(db: &Db, task_id: &str) -> Result<Task> {
+    db.tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .cloned()
+        .map(task_with_iso_timestamps)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))
+}
+
+#[tauri::command]
+fn get_task(app: AppHandle, task_id: String) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        get_task_inner(&db, &task_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoteOverduePayload {
+    pub board_id: String,
+    pub from_column: String,
+    pub to_column: String,
+}
+
+fn promote_overdue_inner(
+    db: &mut Db,
+    payload: &PromoteOverduePayload,
+    today: chrono::NaiveDate,
+) -> Result<Vec<String>> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == payload.board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(payload.board_id.clone()))?;
+    for column in [&payload.from_column, &payload.to_column] {
+        if !board.columns.contains(column) {
+            return Err(VaultError::ColumnNotFound(
+                payload.board_id.clone(),
+                column.clone(),
+            ));
+        }
+    }
+
+    let now = now_iso();
+    let mut moved = Vec::new();
+    for task in db.tasks.iter_mut().filter(|t| {
+        t.board == payload.board_id
+            && t.column == payload.from_column
+            && due_bucket_kind(t.due.as_deref(), today) == DueBucketKind::Overdue
+    }) {
+        set_task_column(task, &payload.to_column, &now);
+        moved.push(task.id.clone());
+    }
+    Ok(moved)
+}
+
+#[tauri::command]
+fn promote_overdue(
+    app: AppHandle,
+    payload: PromoteOverduePayload,
+) -> std::result::Result<Vec<String>, ErrorResponse> {
+    (|| -> Result<Vec<String>> {
+        let moved = with_locked_db_if(&db_path(&app)?, |db| {
+            let moved = promote_overdue_inner(db, &payload, today())?;
+            let changed = !moved.is_empty();
+            Ok((moved, changed))
+        })?;
+        if !moved.is_empty() {
+            log_activity(
+                &app,
+                "promote_overdue",
+                "board",
+                &payload.board_id,
+                json!({ "to": payload.to_column, "taskIds": moved }),
+            );
+        }
+        Ok(moved)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn sorted_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            // Rebuild through a BTreeMap so key order holds even if serde_json preserves insertion order.
+            let sorted = map
+                .into_iter()
+                .map(|(k, v)| (k, sorted_json(v)))
+                .collect::<BTreeMap<_, _>>();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sorted_json).collect())
+        }
+        other => other,
+    }
+}
+
+fn task_canonical_inner(task: &Task) -> Result<String> {
+    let mut task = task_with_iso_timestamps(task.clone());
+    task.body = task.body.trim().to_string();
+    let value = sorted_json(serde_json::to_value(&task)?);
+    Ok(serde_json::to_string(&value)?)
+}
+
+#[tauri::command]
+fn task_canonical(app: AppHandle, task_id: String) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let task = db
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| VaultError::TaskNotFound(task_id.clone()))?;
+        task_canonical_inner(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+// Keeps a title on one line and stops it from opening a heading, list, quote or inline span.
+fn escape_markdown(raw: &str) -> String {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = String::with_capacity(collapsed.len());
+    for (i, c) in collapsed.chars().enumerate() {
+        let special = matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '|' | '~')
+            || (i == 0 && matches!(c, '#' | '-' | '+' | '>'));
+        if special {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn render_board_markdown(board: &BoardWithTasks) -> String {
+    let mut out = format!("# {}\n", escape_markdown(&board.board.title));
+    for column in &board.columns {
+        out.push_str(&format!("\n## {}\n\n", escape_markdown(&column.name)));
+        if column.tasks.is_empty() {
+            out.push_str("_No tasks_\n");
+            continue;
+        }
+        let check = if is_done_column(&board.board, &column.name) {
+            "x"
+        } else {
+            " "
+        };
+        for task in &column.tasks {
+            let mut line = format!("- [{check}] {}", escape_markdown(&task.title));
+            if let Some(due) = &task.due {
+                line.push_str(&format!(" (due {due})"));
+            }
+            for tag in &task.tags {
+                line.push_str(&format!(" `#{tag}`"));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[tauri::command]
+fn export_board_markdown(
+    app: AppHandle,
+    board_id: String,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let board = board_with_tasks_inner(&db, &board_id)?;
+        Ok(render_board_markdown(&board))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+// 2 added `archive`; version 1 bundles still import with an empty archive.
+const VAULT_EXPORT_SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultExport {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub boards: Vec<Board>,
+    pub tasks: Vec<Task>,
+    pub projects: Vec<Project>,
+    pub epics: Vec<Epic>,
+    #[serde(default)]
+    pub archive: Vec<Task>,
+}
+
+fn export_vault_inner(db: &Db) -> VaultExport {
+    VaultExport {
+        schema_version: VAULT_EXPORT_SCHEMA_VERSION,
+        exported_at: now_iso(),
+        boards: list_boards_inner(db),
+        tasks: list_tasks_inner(db, None),
+        projects: list_projects_inner(db),
+        epics: list_epics_inner(db, None),
+        archive: db
+            .archive
+            .iter()
+            .cloned()
+            .map(task_with_iso_timestamps)
+            .collect(),
+    }
+}
+
+#[tauri::command]
+fn export_vault_json(
+    app: AppHandle,
+    dest_path: Option<String>,
+) -> std::result::Result<VaultExport, ErrorResponse> {
+    (|| -> Result<VaultExport> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let export = export_vault_inner(&db);
+        if let Some(dest) = dest_path {
+            let raw = serde_json::to_string_pretty(&export)?;
+            write_atomic(Path::new(&dest), raw.as_bytes())?;
+        }
+        Ok(export)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub created: usize,
+    pub skipped: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+}
+
+fn import_entities<T>(
+    existing: &mut Vec<T>,
+    incoming: Vec<T>,
+    mode: ImportMode,
+    id: impl Fn(&mut T) -> &mut String,
+    summary: &mut ImportSummary,
+) -> HashMap<String, String> {
+    let mut renames = HashMap::new();
+    for mut entity in incoming {
+        let original = id(&mut entity).clone();
+        let position = existing.iter_mut().position(|e| *id(e) == original);
+        match (position, mode) {
+            (None, _) => summary.created += 1,
+            (Some(_), ImportMode::Skip) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(index), ImportMode::Overwrite) => {
+                existing[index] = entity;
+                summary.overwritten += 1;
+                continue;
+            }
+            (Some(_), ImportMode::Rename) => {
+                let mut suffix = 1;
+                let renamed = loop {
+                    let candidate = if suffix == 1 {
+                        format!("{original}-imported")
+                    } else {
+                        format!("{original}-imported-{suffix}")
+                    };
+                    if !existing.iter_mut().any(|e| *id(e) == candidate) {
+                        break candidate;
+                    }
+                    suffix += 1;
+                };
+                *id(&mut entity) = renamed.clone();
+                renames.insert(original, renamed);
+                summary.renamed += 1;
+            }
+        }
+        existing.push(entity);
+    }
+    renames
+}
+
+fn validate_import(bundle: &VaultExport) -> Result<()> {
+    if bundle.schema_version > VAULT_EXPORT_SCHEMA_VERSION {
+        return Err(VaultError::InvalidData(format!(
+            "unsupported export schema version {} (expected at most {VAULT_EXPORT_SCHEMA_VERSION})",
+            bundle.schema_version
+        )));
+    }
+    let entities = bundle
+        .boards
+        .iter()
+        .map(|b| ("board", &b.id, &b.title))
+        .chain(
+            bundle
+                .tasks
+                .iter()
+                .chain(bundle.archive.iter())
+                .map(|t| ("task", &t.id, &t.title)),
+        )
+        .chain(bundle.projects.iter().map(|p| ("project", &p.id, &p.title)))
+        .chain(bundle.epics.iter().map(|e| ("epic", &e.id, &e.title)));
+    for (kind, id, title) in entities {
+        sanitize_id(id)?;
+        if title.trim().is_empty() {
+            return Err(VaultError::InvalidData(format!("{kind} {id} has no title")));
+        }
+    }
+    Ok(())
+}
+
+fn import_vault_inner(db: &mut Db, bundle: VaultExport, mode: ImportMode) -> Result<ImportSummary> {
+    validate_import(&bundle)?;
+    // Import into a copy so a record that fails validation leaves the vault untouched.
+    let mut next = db.clone();
+    let mut summary = ImportSummary::default();
+
+    let board_ids = import_entities(
+        &mut next.boards,
+        bundle.boards,
+        mode,
+        |b| &mut b.id,
+        &mut summary,
+    );
+    let project_ids = import_entities(
+        &mut next.projects,
+        bundle.projects,
+        mode,
+        |p| &mut p.id,
+        &mut summary,
+    );
+    let mut epics = bundle.epics;
+    for epic in &mut epics {
+        if let Some(renamed) = epic.project_id.as_ref().and_then(|p| project_ids.get(p)) {
+            epic.project_id = Some(renamed.clone());
+        }
+    }
+    let epic_ids = import_entities(&mut next.epics, epics, mode, |e| &mut e.id, &mut summary);
+
+    // Live and archived tasks share one id space, so both are imported as a single list
+    // tagged with where each entry lives.
+    let mut tasks = bundle
+        .tasks
+        .into_iter()
+        .map(|t| (t, false))
+        .chain(bundle.archive.into_iter().map(|t| (t, true)))
+        .collect::<Vec<_>>();
+    for (task, archived) in &mut tasks {
+        if let Some(renamed) = board_ids.get(&task.board) {
+            task.board = renamed.clone();
+        }
+        if let Some(renamed) = task.project_id.as_ref().and_then(|p| project_ids.get(p)) {
+            task.project_id = Some(renamed.clone());
+        }
+        if let Some(renamed) = task.epic_id.as_ref().and_then(|e| epic_ids.get(e)) {
+            task.epic_id = Some(renamed.clone());
+        }
+        // An archived card may sit in a column that has since been removed; unarchiving handles that.
+        if *archived {
+            continue;
+        }
+        let board = next
+            .boards
+            .iter()
+            .find(|b| b.id == task.board)
+            .ok_or_else(|| VaultError::BoardNotFound(task.board.clone()))?;
+        if !board.columns.contains(&task.column) {
+            return Err(VaultError::ColumnNotFound(
+                task.board.clone(),
+                task.column.clone(),
+            ));
+        }
+    }
+    let mut existing = std::mem::take(&mut next.tasks)
+        .into_iter()
+        .map(|t| (t, false))
+        .chain(
+            std::mem::take(&mut next.archive)
+                .into_iter()
+                .map(|t| (t, true)),
+        )
+        .collect::<Vec<_>>();
+    let appended_from = existing.len();
+    let task_ids = import_entities(&mut existing, tasks, mode, |(t, _)| &mut t.id, &mut summary);
+    // Only rename mode changes ids, and it appends every imported task, so blockers
+    // pointing at renamed cards are fixed up in the appended tail.
+    for (task, _) in &mut existing[appended_from..] {
+        for blocker in &mut task.blocked_by {
+            if let Some(renamed) = task_ids.get(blocker) {
+                *blocker = renamed.clone();
+            }
+        }
+    }
+    for (task, archived) in existing {
+        if archived {
+            next.archive.push(task);
+        } else {
+            next.tasks.push(task);
+        }
+    }
+
+    *db = next;
+    Ok(summary)
+}
+
+#[tauri::command]
+fn import_vault_json(
+    app: AppHandle,
+    bundle: VaultExport,
+    mode: ImportMode,
+) -> std::result::Result<ImportSummary, ErrorResponse> {
+    (|| -> Result<ImportSummary> {
+        let summary = with_locked_db(&db_path(&app)?, |db| import_vault_inner(db, bundle, mode))?;
+        log_activity(
+            &app,
+            "import",
+            "vault",
+            "",
+            json!({ "mode": mode, "summary": summary }),
+        );
+        Ok(summary)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn archive_task_inner(db: &mut Db, task_id: &str) -> Result<Task> {
+    let index = db
+        .tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    let mut task = db.tasks.remove(index);
+    let now = now_iso();
+    task.archived = Some(now.clone());
+    task.updated = Some(now);
+    db.archive.push(task.clone());
+    Ok(task_with_iso_timestamps(task))
+}
+
+fn unarchive_task_inner(db: &mut Db, task_id: &str) -> Result<Task> {
+    let index = db
+        .archive
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    if db.tasks.iter().any(|t| t.id == task_id) {
+        return Err(VaultError::InvalidData(format!(
+            "a live task already uses id {task_id}"
+        )));
+    }
+    let archived = &db.archive[index];
+    let column = restorable_column(db, &archived.board, &archived.column)?;
+    let mut task = db.archive.remove(index);
+    task.column = column;
+    task.archived = None;
+    task.updated = Some(now_iso());
+    db.tasks.push(task.clone());
+    Ok(task_with_iso_timestamps(task))
+}
+
+#[tauri::command]
+fn archive_task(app: AppHandle, task_id: String) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| archive_task_inner(db, &task_id))?;
+        log_activity(&app, "archive", "task", &task.id, json!({}));
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn unarchive_task(app: AppHandle, task_id: String) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| unarchive_task_inner(db, &task_id))?;
+        log_activity(&app, "unarchive", "task", &task.id, json!({}));
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn list_archived_tasks_inner(db: &Db) -> Vec<Task> {
+    let mut tasks = db
+        .archive
+        .iter()
+        .cloned()
+        .map(task_with_iso_timestamps)
+        .collect::<Vec<_>>();
+    // RFC 3339 UTC stamps sort chronologically as strings; newest first.
+    tasks.sort_by(|a, b| b.archived.cmp(&a.archived));
+    tasks
+}
+
+#[tauri::command]
+fn list_archived_tasks(app: AppHandle) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(list_archived_tasks_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn checklist_item_mut(task: &mut Task, index: usize) -> Result<&mut ChecklistItem> {
+    let len = task.checklist.len();
+    task.checklist.get_mut(index).ok_or_else(|| {
+        VaultError::InvalidData(format!(
+            "checklist item {index} out of range (task has {len})"
+        ))
+    })
+}
+
+fn edit_task_inner(
+    db: &mut Db,
+    task_id: &str,
+    edit: impl FnOnce(&mut Task) -> Result<()>,
+) -> Result<Task> {
+    let task = db
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    edit(task)?;
+    task.updated = Some(now_iso());
+    Ok(task_with_iso_timestamps(task.clone()))
+}
+
+fn toggle_checklist_item_inner(db: &mut Db, task_id: &str, index: usize) -> Result<Task> {
+    edit_task_inner(db, task_id, |task| {
+        let item = checklist_item_mut(task, index)?;
+        item.done = !item.done;
+        Ok(())
+    })
+}
+
+fn add_checklist_item_inner(db: &mut Db, task_id: &str, text: &str) -> Result<Task> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(VaultError::InvalidData(
+            "checklist item text is empty".to_string(),
+        ));
+    }
+    edit_task_inner(db, task_id, |task| {
+        task.checklist.push(ChecklistItem {
+            text: text.to_string(),
+            done: false,
+        });
+        Ok(())
+    })
+}
+
+fn add_checklist_item_at(path: &Path, task_id: &str, text: &str) -> Result<Task> {
+    with_locked_db(path, |db| add_checklist_item_inner(db, task_id, text))
+}
+
+fn remove_checklist_item_inner(db: &mut Db, task_id: &str, index: usize) -> Result<Task> {
+    edit_task_inner(db, task_id, |task| {
+        checklist_item_mut(task, index)?;
+        task.checklist.remove(index);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn toggle_checklist_item(
+    app: AppHandle,
+    task_id: String,
+    index: usize,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            toggle_checklist_item_inner(db, &task_id, index)
+        })?;
+        log_activity(
+            &app,
+            "toggle_checklist_item",
+            "task",
+            &task.id,
+            json!({ "index": index }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn add_checklist_item(
+    app: AppHandle,
+    task_id: String,
+    text: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = add_checklist_item_at(&db_path(&app)?, &task_id, &text)?;
+        log_activity(
+            &app,
+            "add_checklist_item",
+            "task",
+            &task.id,
+            json!({ "text": text }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn remove_checklist_item(
+    app: AppHandle,
+    task_id: String,
+    index: usize,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            remove_checklist_item_inner(db, &task_id, index)
+        })?;
+        log_activity(
+            &app,
+            "remove_checklist_item",
+            "task",
+            &task.id,
+            json!({ "index": index }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicProgress {
+    pub total: usize,
+    pub done: usize,
+    pub percent: u32,
+}
+
+/// Without an explicit `done_column`, each story's own board decides what counts as done.
+fn epic_progress_inner(db: &Db, epic_id: &str, done_column: Option<&str>) -> Result<EpicProgress> {
+    if !db.epics.iter().any(|e| e.id == epic_id) {
+        return Err(VaultError::EpicNotFound(epic_id.to_string()));
+    }
+    let stories = db
+        .tasks
+        .iter()
+        .filter(|t| t.epic_id.as_deref() == Some(epic_id));
+    let (total, done) = stories.fold((0, 0), |(total, done), task| {
+        let is_done = match done_column {
+            Some(column) => task.column == column,
+            None => done_columns_of(db, &task.board).contains(&task.column),
+        };
+        (total + 1, done + usize::from(is_done))
+    });
+    let percent = (done * 100).checked_div(total).unwrap_or(0) as u32;
+    Ok(EpicProgress {
+        total,
+        done,
+        percent,
+    })
+}
+
+#[tauri::command]
+fn get_epic_progress(
+    app: AppHandle,
+    epic_id: String,
+    done_column: Option<String>,
+) -> std::result::Result<EpicProgress, ErrorResponse> {
+    (|| -> Result<EpicProgress> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        epic_progress_inner(&db, &epic_id, done_column.as_deref())
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateId {
+    pub id: String,
+    pub locations: Vec<String>,
+}
+
+fn find_duplicate_ids_inner(db: &Db) -> Vec<DuplicateId> {
+    fn located<'a>(
+        collection: &'static str,
+        ids: impl Iterator<Item = &'a str>,
+    ) -> impl Iterator<Item = (&'a str, String)> {
+        ids.enumerate()
+            .map(move |(index, id)| (id, format!("{collection}[{index}]")))
+    }
+
+    let entries = located("boards", db.boards.iter().map(|b| b.id.as_str()))
+        .chain(located("tasks", db.tasks.iter().map(|t| t.id.as_str())))
+        .chain(located(
+            "projects",
+            db.projects.iter().map(|p| p.id.as_str()),
+        ))
+        .chain(located("epics", db.epics.iter().map(|e| e.id.as_str())))
+        .chain(located("archive", db.archive.iter().map(|t| t.id.as_str())));
+    let mut seen: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (id, location) in entries {
+        seen.entry(id).or_default().push(location);
+    }
+
+    seen.into_iter()
+        .filter(|(_, locations)| locations.len() > 1)
+        .map(|(id, locations)| DuplicateId {
+            id: id.to_string(),
+            locations,
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn find_duplicate_ids(app: AppHandle) -> std::result::Result<Vec<DuplicateId>, ErrorResponse> {
+    (|| -> Result<Vec<DuplicateId>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(find_duplicate_ids_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteProjectSummary {
+    pub project_id: String,
+    pub epics_removed: Vec<String>,
+    pub tasks_updated: Vec<String>,
+}
+
+fn delete_project_inner(
+    db: &mut Db,
+    project_id: &str,
+    cascade: bool,
+) -> Result<DeleteProjectSummary> {
+    let index = db
+        .projects
+        .iter()
+        .position(|p| p.id == project_id)
+        .ok_or_else(|| VaultError::ProjectNotFound(project_id.to_string()))?;
+    let epic_ids = db
+        .epics
+        .iter()
+        .filter(|e| e.project_id.as_deref() == Some(project_id))
+        .map(|e| e.id.clone())
+        .collect::<Vec<_>>();
+    if !cascade && !epic_ids.is_empty() {
+        return Err(VaultError::InvalidData(format!(
+            "project {project_id} still has {} linked epic(s); delete with cascade to remove them",
+            epic_ids.len()
+        )));
+    }
+
+    db.projects.remove(index);
+    db.epics.retain(|e| !epic_ids.contains(&e.id));
+    let now = now_iso();
+    let mut tasks_updated = Vec::new();
+    for task in db.tasks.iter_mut().chain(db.archive.iter_mut()) {
+        let linked_project = task.project_id.as_deref() == Some(project_id);
+        let linked_epic = task
+            .epic_id
+            .as_ref()
+            .map(|e| epic_ids.contains(e))
+            .unwrap_or(false);
+        if !linked_project && !linked_epic {
+            continue;
+        }
+        if linked_project {
+            task.project_id = None;
+        }
+        if linked_epic {
+            task.epic_id = None;
+        }
+        task.updated = Some(now.clone());
+        tasks_updated.push(task.id.clone());
+    }
+
+    Ok(DeleteProjectSummary {
+        project_id: project_id.to_string(),
+        epics_removed: epic_ids,
+        tasks_updated,
+    })
+}
+
+#[tauri::command]
+fn delete_project(
+    app: AppHandle,
+    project_id: String,
+    cascade: bool,
+) -> std::result::Result<DeleteProjectSummary, ErrorResponse> {
+    (|| -> Result<DeleteProjectSummary> {
+        let summary = with_locked_db(&db_path(&app)?, |db| {
+            delete_project_inner(db, &project_id, cascade)
+        })?;
+        log_activity(
+            &app,
+            "delete",
+            "project",
+            &project_id,
+            json!({ "cascade": cascade, "summary": summary }),
+        );
+        Ok(summary)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProjectPayload {
+    pub id: String,
+    pub title: Option<String>,
+    pub owner: Option<String>,
+    pub description: Option<String>,
+}
+
+fn update_project_inner(db: &mut Db, payload: UpdateProjectPayload) -> Result<Project> {
+    let project = db
+        .projects
+        .iter_mut()
+        .find(|p| p.id == payload.id)
+        .ok_or_else(|| VaultError::ProjectNotFound(payload.id.clone()))?;
+    if let Some(title) = payload.title {
+        project.title = non_empty(title)
+            .ok_or_else(|| VaultError::InvalidData("project title is empty".to_string()))?;
+    }
+    // An empty string clears an optional field; omitting it leaves the value alone.
+    if let Some(owner) = payload.owner {
+        project.owner = non_empty(owner);
+    }
+    if let Some(description) = payload.description {
+        project.description = non_empty(description);
+    }
+    project.updated = Some(now_iso());
+    Ok(project_with_iso_timestamps(project.clone()))
+}
+
+#[tauri::command]
+fn update_project(
+    app: AppHandle,
+    payload: UpdateProjectPayload,
+) -> std::result::Result<Project, ErrorResponse> {
+    (|| -> Result<Project> {
+        let project = with_locked_db(&db_path(&app)?, |db| update_project_inner(db, payload))?;
+        log_activity(&app, "update", "project", &project.id, json!({}));
+        Ok(project)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEpicPayload {
+    pub id: String,
+    pub title: Option<String>,
+    pub project_id: Option<String>,
+    pub owner: Option<String>,
+    pub description: Option<String>,
+}
+
+fn update_epic_inner(db: &mut Db, payload: UpdateEpicPayload) -> Result<Epic> {
+    let project_id = payload.project_id.map(non_empty);
+    if let Some(Some(project_id)) = &project_id {
+        if !db.projects.iter().any(|p| &p.id == project_id) {
+            return Err(VaultError::ProjectNotFound(project_id.clone()));
+        }
+    }
+    let epic = db
+        .epics
+        .iter_mut()
+        .find(|e| e.id == payload.id)
+        .ok_or_else(|| VaultError::EpicNotFound(payload.id.clone()))?;
+    if let Some(title) = payload.title {
+        epic.title = non_empty(title)
+            .ok_or_else(|| VaultError::InvalidData("epic title is empty".to_string()))?;
+    }
+    if let Some(project_id) = project_id {
+        epic.project_id = project_id;
+    }
+    if let Some(owner) = payload.owner {
+        epic.owner = non_empty(owner);
+    }
+    if let Some(description) = payload.description {
+        epic.description = non_empty(description);
+    }
+    epic.updated = Some(now_iso());
+    Ok(epic_with_iso_timestamps(epic.clone()))
+}
+
+#[tauri::command]
+fn update_epic(
+    app: AppHandle,
+    payload: UpdateEpicPayload,
+) -> std::result::Result<Epic, ErrorResponse> {
+    (|| -> Result<Epic> {
+        let epic = with_locked_db(&db_path(&app)?, |db| update_epic_inner(db, payload))?;
+        log_activity(&app, "update", "epic", &epic.id, json!({}));
+        Ok(epic)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn duplicate_task_inner(db: &mut Db, task_id: &str, slug_ids: bool) -> Result<Task> {
+    let source = db
+        .tasks
+        .iter()
+        .chain(db.archive.iter())
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    let title = format!("{} (copy)", source.title);
+    let copy = Task {
+        id: new_entity_id(db, "story", &title, slug_ids),
+        title,
+        created: Some(now_iso()),
+        updated: None,
+        archived: None,
+        // The copy starts its own history rather than inheriting the source's moves.
+        transitions: vec![],
+        // Attachment files live under the source's id, so they aren't shared.
+        attachments: vec![],
+        ..source.clone()
+    };
+    db.tasks.push(copy.clone());
+    Ok(copy)
+}
+
+#[tauri::command]
+fn duplicate_task(app: AppHandle, task_id: String) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let slug_ids = slug_ids_enabled(&app)?;
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            duplicate_task_inner(db, &task_id, slug_ids)
+        })?;
+        log_activity(
+            &app,
+            "duplicate",
+            "task",
+            &task.id,
+            json!({ "source": task_id }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const AUTOFILL_DELTA_EVENT: &str = "autofill://delta";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AutofillDelta {
+    pub delta: String,
+}
+
+/// Accumulates the server-sent events of a streamed chat completion.
+#[derive(Debug, Default)]
+struct AutofillStream {
+    pending: Vec<u8>,
+    content: String,
+    usage_chunk: Option<serde_json::Value>,
+    error: Option<String>,
+    done: bool,
+}
+
+impl AutofillStream {
+    /// Feeds raw response bytes and returns the content deltas completed by them.
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut deltas = Vec::new();
+        // Chunks can split lines (and UTF-8 sequences), so only whole lines are decoded.
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line = self.pending.drain(..=end).collect::<Vec<_>>();
+            deltas.extend(self.handle_line(&String::from_utf8_lossy(&line)));
+        }
+        deltas
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        let rest = std::mem::take(&mut self.pending);
+        self.handle_line(&String::from_utf8_lossy(&rest))
+            .into_iter()
+            .collect()
+    }
+
+    fn handle_line(&mut self, line: &str) -> Option<String> {
+        let data = line.trim().strip_prefix("data:")?.trim();
+        if data == "[DONE]" {
+            self.done = true;
+            return None;
+        }
+        let value = match serde_json::from_str::<serde_json::Value>(data) {
+            Ok(value) => value,
+            Err(e) => {
+                self.error.get_or_insert_with(|| {
+                    format!("malformed stream chunk ({e}): {}", response_snippet(data))
+                });
+                return None;
+            }
+        };
+        if let Some(error) = value.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| error.to_string());
+            self.error.get_or_insert(message);
+            return None;
+        }
+        if value.get("usage").is_some_and(|u| !u.is_null()) {
+            self.usage_chunk = Some(value.clone());
+        }
+        let delta = value
+            .pointer("/choices/0/delta/content")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())?;
+        self.content.push_str(delta);
+        Some(delta.to_string())
+    }
+
+    fn into_response(self) -> Result<OpenAiAutoFillResponse> {
+        if let Some(error) = self.error {
+            return Err(VaultError::InvalidData(format!(
+                "OpenAI stream failed: {error}"
+            )));
+        }
+        if !self.done && self.content.is_empty() {
+            return Err(VaultError::InvalidData(
+                "OpenAI stream ended before any content arrived".to_string(),
+            ));
+        }
+        parse_autofill_content(&self.content)
+    }
+}
+
+#[tauri::command]
+async fn openai_autofill_story_stream(
+    app: AppHandle,
+    usage: tauri::State<'_, OpenAiUsageState>,
+    payload: OpenAiAutoFillPayload,
+) -> std::result::Result<OpenAiAutoFillResponse, ErrorResponse> {
+    (async move {
+        if openai_mock_enabled() {
+            let content = mock_autofill_content(&payload);
+            let _ = app.emit(
+                AUTOFILL_DELTA_EVENT,
+                AutofillDelta {
+                    delta: content.clone(),
+                },
+            );
+            return parse_autofill_content(&content);
+        }
+        let (mut response, model) = request_autofill(&app, &payload, true).await?;
+        let mut stream = AutofillStream::default();
+        let emit = |deltas: Vec<String>| {
+            for delta in deltas {
+                let _ = app.emit(AUTOFILL_DELTA_EVENT, AutofillDelta { delta });
+            }
+        };
+        while let Some(bytes) = response.chunk().await.map_err(openai_request_error)? {
+            emit(stream.push(&bytes));
+        }
+        emit(stream.finish());
+        if let (Some(chunk), Ok(mut totals)) = (&stream.usage_chunk, usage.0.lock()) {
+            record_openai_usage(&mut totals, chunk, &model);
+        }
+        stream.into_response()
+    })
+    .await
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskWithBoard {
+    #[serde(flatten)]
+    pub task: Task,
+    pub board_title: Option<String>,
+    pub orphaned: bool,
+}
+
+fn list_all_tasks_across_boards_inner(db: &Db) -> Vec<TaskWithBoard> {
+    let titles = list_boards_inner(db)
+        .into_iter()
+        .map(|b| (b.id, b.title))
+        .collect::<HashMap<_, _>>();
+    let mut tasks = list_tasks_inner(db, None)
+        .into_iter()
+        .map(|task| {
+            let board_title = titles.get(&task.board).cloned();
+            TaskWithBoard {
+                orphaned: board_title.is_none(),
+                board_title,
+                task,
+            }
+        })
+        .collect::<Vec<_>>();
+    // Dated tasks first (soonest first), then undated ones; title breaks ties.
+    tasks.sort_by(|a, b| {
+        let due = |t: &TaskWithBoard| t.task.due.as_deref().and_then(parse_due);
+        match (due(a), due(b)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| a.task.title.cmp(&b.task.title))
+    });
+    tasks
+}
+
+#[tauri::command]
+fn list_all_tasks_across_boards(
+    app: AppHandle,
+) -> std::result::Result<Vec<TaskWithBoard>, ErrorResponse> {
+    (|| -> Result<Vec<TaskWithBoard>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(list_all_tasks_across_boards_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn normalize_due(due: Option<&str>) -> Result<Option<String>> {
+    match due.map(str::trim).filter(|d| !d.is_empty()) {
+        None => Ok(None),
+        Some(raw) => {
+            let date = parse_due(raw)
+                .ok_or_else(|| VaultError::InvalidData(format!("unrecognized due date: {raw}")))?;
+            Ok(Some(date.format("%Y-%m-%d").to_string()))
+        }
+    }
+}
+
+fn set_task_due_inner(db: &mut Db, task_id: &str, due: Option<&str>) -> Result<Task> {
+    let due = normalize_due(due)?;
+    edit_task_inner(db, task_id, |task| {
+        task.due = due;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn set_task_due(
+    app: AppHandle,
+    task_id: String,
+    due: Option<String>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            set_task_due_inner(db, &task_id, due.as_deref())
+        })?;
+        log_activity(
+            &app,
+            "set_due",
+            "task",
+            &task.id,
+            json!({ "due": task.due }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn tag_matches(a: &str, b: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        a.to_lowercase() == b.to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+fn dedupe_tags(tags: &mut Vec<String>, case_insensitive: bool) {
+    let mut kept: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.drain(..) {
+        if !kept.iter().any(|k| tag_matches(k, &tag, case_insensitive)) {
+            kept.push(tag);
+        }
+    }
+    *tags = kept;
+}
+
+fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(VaultError::InvalidData("tag is empty".to_string()));
+    }
+    Ok(tag.to_string())
+}
+
+fn add_task_tag_inner(
+    db: &mut Db,
+    task_id: &str,
+    tag: &str,
+    case_insensitive: bool,
+) -> Result<Task> {
+    let tag = normalize_tag(tag)?;
+    edit_task_inner(db, task_id, |task| {
+        task.tags.push(tag);
+        dedupe_tags(&mut task.tags, case_insensitive);
+        Ok(())
+    })
+}
+
+fn remove_task_tag_inner(
+    db: &mut Db,
+    task_id: &str,
+    tag: &str,
+    case_insensitive: bool,
+) -> Result<Task> {
+    let tag = normalize_tag(tag)?;
+    edit_task_inner(db, task_id, |task| {
+        task.tags
+            .retain(|t| !tag_matches(t, &tag, case_insensitive));
+        Ok(())
+    })
+}
+
+fn rename_tag_inner(db: &mut Db, from: &str, to: &str, case_insensitive: bool) -> Result<usize> {
+    let to = normalize_tag(to)?;
+    let now = now_iso();
+    let mut updated = 0;
+    for task in db.tasks.iter_mut().chain(db.archive.iter_mut()) {
+        if !task
+            .tags
+            .iter()
+            .any(|t| tag_matches(t, from, case_insensitive))
+        {
+            continue;
+        }
+        for t in task.tags.iter_mut() {
+            if tag_matches(t, from, case_insensitive) {
+                *t = to.clone();
+            }
+        }
+        dedupe_tags(&mut task.tags, case_insensitive);
+        task.updated = Some(now.clone());
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+#[tauri::command]
+fn add_task_tag(
+    app: AppHandle,
+    task_id: String,
+    tag: String,
+    case_insensitive: Option<bool>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            add_task_tag_inner(db, &task_id, &tag, case_insensitive.unwrap_or(false))
+        })?;
+        log_activity(&app, "add_tag", "task", &task.id, json!({ "tag": tag }));
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn remove_task_tag(
+    app: AppHandle,
+    task_id: String,
+    tag: String,
+    case_insensitive: Option<bool>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            remove_task_tag_inner(db, &task_id, &tag, case_insensitive.unwrap_or(false))
+        })?;
+        log_activity(&app, "remove_tag", "task", &task.id, json!({ "tag": tag }));
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn rename_tag(
+    app: AppHandle,
+    from: String,
+    to: String,
+    case_insensitive: Option<bool>,
+) -> std::result::Result<usize, ErrorResponse> {
+    (|| -> Result<usize> {
+        let updated = with_locked_db_if(&db_path(&app)?, |db| {
+            let updated = rename_tag_inner(db, &from, &to, case_insensitive.unwrap_or(false))?;
+            Ok((updated, updated > 0))
+        })?;
+        if updated > 0 {
+            log_activity(
+                &app,
+                "rename_tag",
+                "tag",
+                &from,
+                json!({ "to": to, "tasks": updated }),
+            );
+        }
+        Ok(updated)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+fn list_tags_inner(db: &Db, board_id: Option<&str>, include_archived: bool) -> Vec<TagCount> {
+    let live = list_tasks_inner(db, board_id);
+    let archived = db
+        .archive
+        .iter()
+        .filter(|t| include_archived && board_id.map(|b| b == t.board).unwrap_or(true));
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in live.iter().chain(archived) {
+        let mut seen = Vec::new();
+        for tag in &task.tags {
+            // A tag repeated on one task still counts that task once.
+            if !seen.contains(&tag) {
+                seen.push(tag);
+                *counts.entry(tag.as_str()).or_default() += 1;
+            }
+        }
+    }
+    let mut tags = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount {
+            tag: tag.to_string(),
+            count,
+        })
+        .collect::<Vec<_>>();
+    // BTreeMap iteration is already by name, so a stable sort keeps names ordered within a count.
+    tags.sort_by_key(|t| std::cmp::Reverse(t.count));
+    tags
+}
+
+#[tauri::command]
+fn list_tags(
+    app: AppHandle,
+    board_id: Option<String>,
+    include_archived: Option<bool>,
+) -> std::result::Result<Vec<TagCount>, ErrorResponse> {
+    (|| -> Result<Vec<TagCount>> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        Ok(list_tags_inner(
+            &db,
+            board_id.as_deref(),
+            include_archived.unwrap_or(false),
+        ))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkMoveColumnPayload {
+    pub board_id: String,
+    pub from_column: String,
+    pub to_column: String,
+}
+
+fn bulk_move_column_inner(db: &mut Db, payload: &BulkMoveColumnPayload) -> Result<Vec<String>> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == payload.board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(payload.board_id.clone()))?;
+    if !board.columns.contains(&payload.to_column) {
+        return Err(VaultError::ColumnNotFound(
+            payload.board_id.clone(),
+            payload.to_column.clone(),
+        ));
+    }
+
+    let now = now_iso();
+    let mut moved = Vec::new();
+    for task in db
+        .tasks
+        .iter_mut()
+        .filter(|t| t.board == payload.board_id && t.column == payload.from_column)
+    {
+        set_task_column(task, &payload.to_column, &now);
+        moved.push(task.id.clone());
+    }
+    Ok(moved)
+}
+
+#[tauri::command]
+fn bulk_move_column(
+    app: AppHandle,
+    payload: BulkMoveColumnPayload,
+) -> std::result::Result<Vec<String>, ErrorResponse> {
+    (|| -> Result<Vec<String>> {
+        // The whole batch lands in one atomic write of `DB_FILE_NAME`, so it either all persists or none does.
+        let moved = with_locked_db_if(&db_path(&app)?, |db| {
+            let moved = bulk_move_column_inner(db, &payload)?;
+            let changed = !moved.is_empty();
+            Ok((moved, changed))
+        })?;
+        if !moved.is_empty() {
+            log_activity(
+                &app,
+                "bulk_move",
+                "board",
+                &payload.board_id,
+                json!({ "from": payload.from_column, "to": payload.to_column, "taskIds": moved }),
+            );
+        }
+        Ok(moved)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+/// User-editable settings: `defaultColumns` from the vault's `config.json`, the switches from
+/// `app-config.json`. The OpenAI key is managed separately by `set_openai_key`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSettings {
+    pub default_columns: Vec<String>,
+    #[serde(default)]
+    pub use_slug_ids: bool,
+    #[serde(default)]
+    pub auto_commit: bool,
+}
+
+fn vault_settings_from(config: &VaultConfig) -> VaultSettings {
+    VaultSettings {
+        default_columns: default_db_for(config).boards[0].columns.clone(),
+        use_slug_ids: config.use_slug_ids.unwrap_or(false),
+        auto_commit: config.auto_commit.unwrap_or(false),
+    }
+}
+
+fn normalize_columns(columns: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for column in columns {
+        let column = column.trim().to_string();
+        if column.is_empty() {
+            return Err(VaultError::InvalidData(
+                "column names cannot be empty".to_string(),
+            ));
+        }
+        if normalized.contains(&column) {
+            return Err(VaultError::InvalidData(format!(
+                "duplicate column: {column}"
+            )));
+        }
+        normalized.push(column);
+    }
+    if normalized.is_empty() {
+        return Err(VaultError::InvalidData(
+            "a board needs at least one column".to_string(),
+        ));
+    }
+    Ok(normalized)
+}
+
+// Default columns belong to the vault; the id and commit switches are app-wide.
+fn apply_vault_settings(
+    config: &mut VaultConfig,
+    app_config: &mut AppConfig,
+    settings: VaultSettings,
+) -> Result<()> {
+    config.default_columns = Some(normalize_columns(settings.default_columns)?);
+    config.use_slug_ids = None;
+    config.auto_commit = None;
+    app_config.use_slug_ids = Some(settings.use_slug_ids);
+    app_config.auto_commit = Some(settings.auto_commit);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_vault_config(app: AppHandle) -> std::result::Result<VaultSettings, ErrorResponse> {
+    (|| -> Result<VaultSettings> { Ok(vault_settings_from(&effective_config(&app)?)) })()
+        .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn set_vault_config(
+    app: AppHandle,
+    settings: VaultSettings,
+) -> std::result::Result<VaultSettings, ErrorResponse> {
+    (|| -> Result<VaultSettings> {
+        let path = config_path(&app)?;
+        let app_path = app_config_path(&app)?;
+        let mut config = load_config(&path)?;
+        let mut app_config = load_app_config(&app_path)?;
+        apply_vault_settings(&mut config, &mut app_config, settings)?;
+        save_app_config(&app_path, &app_config)?;
+        save_config(&path, &config)?;
+        Ok(vault_settings_from(&with_app_settings(config, &app_config)))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownVault {
+    pub path: String,
+    pub active: bool,
+    pub exists: bool,
+}
+
+fn prepare_vault_dir(path: &str) -> Result<PathBuf> {
+    let dir = expand_path(path)?;
+    if !dir.is_absolute() {
+        return Err(VaultError::InvalidData(format!(
+            "vault path must be absolute: {path}"
+        )));
+    }
+    if dir.exists() && !dir.is_dir() {
+        return Err(VaultError::InvalidData(format!(
+            "vault path is not a directory: {}",
+            dir.display()
+        )));
+    }
+    ensure_db(&dir.join(DB_FILE_NAME))?;
+    Ok(dir)
+}
+
+fn remember_vault(config: &mut AppConfig, dir: &Path) {
+    let path = dir.to_string_lossy().to_string();
+    if !config.known_vaults.contains(&path) {
+        config.known_vaults.push(path.clone());
+    }
+    config.active_vault = Some(path);
+}
+
+fn known_vaults_inner(default: &Path, active: &Path, config: &AppConfig) -> Vec<KnownVault> {
+    let mut paths = vec![default.to_string_lossy().to_string()];
+    for path in &config.known_vaults {
+        if !paths.contains(path) {
+            paths.push(path.clone());
+        }
+    }
+    paths
+        .into_iter()
+        .map(|path| KnownVault {
+            active: Path::new(&path) == active,
+            exists: Path::new(&path).is_dir(),
+            path,
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn set_active_vault(
+    app: AppHandle,
+    active: tauri::State<'_, ActiveVaultState>,
+    path: String,
+) -> std::result::Result<VaultInfo, ErrorResponse> {
+    (|| -> Result<VaultInfo> {
+        // Switching would be saved but never take effect while the override wins in `vault_dir`.
+        if let Some(dir) = vault_dir_override() {
+            return Err(VaultError::InvalidData(format!(
+                "{VAULT_DIR_ENV} is set to {}; unset it to switch vaults",
+                dir.display()
+            )));
+        }
+        let dir = prepare_vault_dir(&path)?;
+        let config_path = app_config_path(&app)?;
+        let mut config = load_app_config(&config_path)?;
+        remember_vault(&mut config, &dir);
+        save_app_config(&config_path, &config)?;
+        *active
+            .0
+            .lock()
+            .map_err(|e| VaultError::InvalidData(e.to_string()))? = Some(dir.clone());
+        // The switch itself succeeded; only live reload is lost if the new vault can't be watched.
+        if let Err(e) = start_vault_watcher(app.clone()) {
+            eprintln!("vault watcher disabled: {e}");
+        }
+        Ok(VaultInfo {
+            path: dir.join(DB_FILE_NAME).to_string_lossy().to_string(),
+        })
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn list_known_vaults(app: AppHandle) -> std::result::Result<Vec<KnownVault>, ErrorResponse> {
+    (|| -> Result<Vec<KnownVault>> {
+        let config = load_app_config(&app_config_path(&app)?)?;
+        Ok(known_vaults_inner(
+            &default_vault_dir(&app)?,
+            &vault_dir(&app)?,
+            &config,
+        ))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn reveal_vault(app: AppHandle, task_id: Option<String>) -> std::result::Result<(), ErrorResponse> {
+    use tauri_plugin_opener::OpenerExt;
+
+    (|| -> Result<()> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let opened = match task_id {
+            // Every task lives in the vault database, so revealing a task selects that file.
+            Some(task_id) => {
+                get_task_inner(&db, &task_id)?;
+                app.opener().reveal_item_in_dir(&path)
+            }
+            None => app
+                .opener()
+                .open_path(vault_dir(&app)?.to_string_lossy(), None::<&str>),
+        };
+        opened.map_err(|e| VaultError::InvalidData(format!("failed to open the vault: {e}")))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn depends_on(db: &Db, from: &str, target: &str) -> bool {
+    let mut seen = Vec::new();
+    let mut stack = vec![from];
+    while let Some(id) = stack.pop() {
+        if id == target {
+            return true;
+        }
+        if seen.contains(&id) {
+            continue;
+        }
+        seen.push(id);
+        if let Some(task) = db.tasks.iter().find(|t| t.id == id) {
+            stack.extend(task.blocked_by.iter().map(String::as_str));
+        }
+    }
+    false
+}
+
+fn add_dependency_inner(db: &mut Db, task_id: &str, depends_on_id: &str) -> Result<Task> {
+    if task_id == depends_on_id {
+        return Err(VaultError::InvalidData(format!(
+            "task cannot depend on itself: {task_id}"
+        )));
+    }
+    if !db.tasks.iter().any(|t| t.id == depends_on_id) {
+        return Err(VaultError::TaskNotFound(depends_on_id.to_string()));
+    }
+    // Adding the edge closes a cycle exactly when the blocker already depends on this task.
+    if depends_on(db, depends_on_id, task_id) {
+        return Err(VaultError::DependencyCycle(format!(
+            "{task_id}, {depends_on_id}"
+        )));
+    }
+    edit_task_inner(db, task_id, |task| {
+        if !task.blocked_by.iter().any(|id| id == depends_on_id) {
+            task.blocked_by.push(depends_on_id.to_string());
+        }
+        Ok(())
+    })
+}
+
+fn remove_dependency_inner(db: &mut Db, task_id: &str, depends_on_id: &str) -> Result<Task> {
+    edit_task_inner(db, task_id, |task| {
+        task.blocked_by.retain(|id| id != depends_on_id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn add_dependency(
+    app: AppHandle,
+    task_id: String,
+    depends_on_id: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            add_dependency_inner(db, &task_id, &depends_on_id)
+        })?;
+        log_activity(
+            &app,
+            "add_dependency",
+            "task",
+            &task.id,
+            json!({ "dependsOn": depends_on_id }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn remove_dependency(
+    app: AppHandle,
+    task_id: String,
+    depends_on_id: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            remove_dependency_inner(db, &task_id, &depends_on_id)
+        })?;
+        log_activity(
+            &app,
+            "remove_dependency",
+            "task",
+            &task.id,
+            json!({ "dependsOn": depends_on_id }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedTask {
+    #[serde(flatten)]
+    pub task: Task,
+    pub open_blockers: Vec<String>,
+}
+
+fn blocked_tasks_inner(db: &Db, board_id: Option<&str>) -> Vec<BlockedTask> {
+    // Blockers that were deleted or archived no longer hold anything up.
+    let is_open = |id: &String| {
+        db.tasks
+            .iter()
+            .any(|t| &t.id == id && !done_columns_of(db, &t.board).contains(&t.column))
+    };
+    db.tasks
+        .iter()
+        .filter(|t| board_id.is_none_or(|board| t.board == board))
+        .filter_map(|task| {
+            let open_blockers = task
+                .blocked_by
+                .iter()
+                .filter(|id| is_open(id))
+                .cloned()
+                .collect::<Vec<_>>();
+            (!open_blockers.is_empty()).then(|| BlockedTask {
+                task: task_with_iso_timestamps(task.clone()),
+                open_blockers,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn get_blocked_tasks(
+    app: AppHandle,
+    board_id: Option<String>,
+) -> std::result::Result<Vec<BlockedTask>, ErrorResponse> {
+    (|| -> Result<Vec<BlockedTask>> {
+        let db = load_db(&db_path(&app)?)?;
+        Ok(blocked_tasks_inner(&db, board_id.as_deref()))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const ACTIVITY_LOG_FILE_NAME: &str = "activity.log";
+const DEFAULT_ACTIVITY_LIMIT: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    pub timestamp: String,
+    pub action: String,
+    pub entity_type: String,
+    pub id: String,
+    #[serde(default)]
+    pub details: serde_json::Value,
+}
+
+fn append_activity(path: &Path, entry: &ActivityEntry) -> Result<()> {
+    use std::io::Write;
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+// A crash mid-append can leave a truncated last line; skip anything that doesn't parse.
+fn read_activity_log(path: &Path, limit: usize) -> Result<Vec<ActivityEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let raw = fs::read_to_string(path)?;
+    Ok(raw
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
+
+/// Best-effort: the mutation is already saved, so a failed log write is not reported.
+fn log_activity(
+    app: &AppHandle,
+    action: &str,
+    entity_type: &str,
+    id: &str,
+    details: serde_json::Value,
+) {
+    let entry = ActivityEntry {
+        timestamp: now_iso(),
+        action: action.to_string(),
+        entity_type: entity_type.to_string(),
+        id: id.to_string(),
+        details,
+    };
+    if let Ok(dir) = vault_dir(app) {
+        if action != UNDO_ACTION {
+            label_change(&dir.join(DB_FILE_NAME), action, entity_type, id);
+        }
+        let _ = append_activity(&dir.join(ACTIVITY_LOG_FILE_NAME), &entry);
+        let auto_commit = effective_config(app)
+            .map(|c| c.auto_commit.unwrap_or(false))
+            .unwrap_or(false);
+        if auto_commit {
+            queue_auto_commit(&dir, format!("{action} {entity_type} {id}"));
+        }
+    }
+}
+
+#[tauri::command]
+fn get_activity_log(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> std::result::Result<Vec<ActivityEntry>, ErrorResponse> {
+    (|| -> Result<Vec<ActivityEntry>> {
+        let path = vault_dir(&app)?.join(ACTIVITY_LOG_FILE_NAME);
+        read_activity_log(&path, limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnTime {
+    pub column: String,
+    /// Seconds we could measure; visits with a missing timestamp add nothing.
+    pub seconds: i64,
+    /// Set when at least one visit to the column could not be timed.
+    pub unknown: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCycleTime {
+    pub task_id: String,
+    pub columns: Vec<ColumnTime>,
+    pub done_at: Option<String>,
+    /// First seen to first arrival in `Done`; `None` while unfinished or when a bound is unknown.
+    pub total_seconds: Option<i64>,
+}
+
+fn parse_instant(raw: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let iso = epoch_to_iso(raw).unwrap_or_else(|| raw.to_string());
+    chrono::DateTime::parse_from_rfc3339(&iso)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+fn task_cycle_time_inner(
+    task: &Task,
+    done_columns: &[String],
+    now: chrono::DateTime<chrono::Utc>,
+) -> TaskCycleTime {
+    let journey = task_column_journey_inner(task);
+    let mut columns: Vec<ColumnTime> = Vec::new();
+    for (i, step) in journey.iter().enumerate() {
+        let start = step.entered_at.as_deref().and_then(parse_instant);
+        let end = match journey.get(i + 1) {
+            Some(next) => next.entered_at.as_deref().and_then(parse_instant),
+            None => Some(now),
+        };
+        let spent = start
+            .zip(end)
+            .map(|(start, end)| (end - start).num_seconds());
+        let index = match columns.iter().position(|c| c.column == step.column) {
+            Some(index) => index,
+            None => {
+                columns.push(ColumnTime {
+                    column: step.column.clone(),
+                    seconds: 0,
+                    unknown: false,
+                });
+                columns.len() - 1
+            }
+        };
+        match spent {
+            Some(seconds) => columns[index].seconds += seconds.max(0),
+            None => columns[index].unknown = true,
+        }
+    }
+
+    // Without any recorded moves, `updated` is the best guess for when a done card got there.
+    let done_at = if task.transitions.is_empty() {
+        done_columns
+            .contains(&task.column)
+            .then(|| task.updated.clone())
+            .flatten()
+    } else {
+        task.transitions
+            .iter()
+            .find(|t| done_columns.contains(&t.to))
+            .map(|t| t.at.clone())
+    };
+    let done_at = done_at.map(|raw| epoch_to_iso(&raw).unwrap_or(raw));
+    let first_seen = task.created.as_deref().and_then(parse_instant);
+    let total_seconds = first_seen
+        .zip(done_at.as_deref().and_then(parse_instant))
+        .map(|(start, end)| (end - start).num_seconds());
+
+    TaskCycleTime {
+        task_id: task.id.clone(),
+        columns,
+        done_at,
+        total_seconds,
+    }
+}
+
+#[tauri::command]
+fn get_task_cycle_time(
+    app: AppHandle,
+    task_id: String,
+) -> std::result::Result<TaskCycleTime, ErrorResponse> {
+    (|| -> Result<TaskCycleTime> {
+        let db = load_db(&db_path(&app)?)?;
+        let task = db
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| VaultError::TaskNotFound(task_id.clone()))?;
+        let done_columns = done_columns_of(&db, &task.board);
+        Ok(task_cycle_time_inner(
+            task,
+            &done_columns,
+            chrono::Utc::now(),
+        ))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DayCount {
+    pub date: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardThroughput {
+    pub board_id: String,
+    pub days: Vec<DayCount>,
+    pub total: usize,
+}
+
+fn parse_day(raw: &str) -> Result<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .map_err(|_| VaultError::InvalidData(format!("expected a YYYY-MM-DD date: {raw}")))
+}
+
+// Each arrival in a done column counts, so a card that was reopened and finished again counts twice.
+fn done_arrivals(task: &Task, board: &Board) -> Vec<chrono::NaiveDate> {
+    if task.transitions.is_empty() {
+        return is_done_column(board, &task.column)
+            .then_some(task.updated.as_deref())
+            .flatten()
+            .and_then(parse_instant)
+            .map(|at| vec![at.date_naive()])
+            .unwrap_or_default();
+    }
+    task.transitions
+        .iter()
+        .filter(|t| is_done_column(board, &t.to))
+        .filter_map(|t| parse_instant(&t.at))
+        .map(|at| at.date_naive())
+        .collect()
+}
+
+fn board_throughput_inner(
+    db: &Db,
+    board_id: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<BoardThroughput> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+    if from > to {
+        return Err(VaultError::InvalidData(format!(
+            "range starts after it ends: {from} > {to}"
+        )));
+    }
+
+    let mut days = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| DayCount {
+            date: day.format("%Y-%m-%d").to_string(),
+            count: 0,
+        })
+        .collect::<Vec<_>>();
+    // Finished cards are often archived, and still count towards throughput.
+    for task in db
+        .tasks
+        .iter()
+        .chain(&db.archive)
+        .filter(|t| t.board == board_id)
+    {
+        for day in done_arrivals(task, board) {
+            if day >= from && day <= to {
+                days[(day - from).num_days() as usize].count += 1;
+            }
+        }
+    }
+
+    Ok(BoardThroughput {
+        board_id: board_id.to_string(),
+        total: days.iter().map(|d| d.count).sum(),
+        days,
+    })
+}
+
+#[tauri::command]
+fn board_throughput(
+    app: AppHandle,
+    board_id: String,
+    from: String,
+    to: String,
+) -> std::result::Result<BoardThroughput, ErrorResponse> {
+    (|| -> Result<BoardThroughput> {
+        let db = load_db(&db_path(&app)?)?;
+        board_throughput_inner(&db, &board_id, parse_day(&from)?, parse_day(&to)?)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBoardPayload {
+    pub board_id: String,
+    pub title: Option<String>,
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatedBoard {
+    #[serde(flatten)]
+    pub board: BoardWithTasks,
+    /// Tasks moved out of columns that no longer exist.
+    pub relocated: Vec<String>,
+}
+
+fn update_board_inner(db: &mut Db, payload: UpdateBoardPayload) -> Result<Vec<String>> {
+    let title =
+        match payload.title {
+            Some(title) => Some(non_empty(title.trim().to_string()).ok_or_else(|| {
+                VaultError::InvalidData("board title cannot be empty".to_string())
+            })?),
+            None => None,
+        };
+    let columns = payload.columns.map(normalize_columns).transpose()?;
+    let board = db
+        .boards
+        .iter_mut()
+        .find(|b| b.id == payload.board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(payload.board_id.clone()))?;
+    if let Some(title) = title {
+        board.title = title;
+    }
+    let Some(columns) = columns else {
+        return Ok(vec![]);
+    };
+    board
+        .wip_limits
+        .retain(|column, _| columns.contains(column));
+    board.done_columns.retain(|column| columns.contains(column));
+    board.columns = columns;
+
+    // Relocation ignores WIP limits: the cards have nowhere else to go.
+    let board = board.clone();
+    let now = now_iso();
+    let mut relocated = Vec::new();
+    for task in db
+        .tasks
+        .iter_mut()
+        .filter(|t| t.board == board.id && !board.columns.contains(&t.column))
+    {
+        set_task_column(task, &board.columns[0], &now);
+        relocated.push(task.id.clone());
+    }
+    Ok(relocated)
+}
+
+#[tauri::command]
+fn update_board(
+    app: AppHandle,
+    payload: UpdateBoardPayload,
+) -> std::result::Result<UpdatedBoard, ErrorResponse> {
+    (|| -> Result<UpdatedBoard> {
+        let board_id = payload.board_id.clone();
+        let updated = with_locked_db(&db_path(&app)?, |db| {
+            let relocated = update_board_inner(db, payload)?;
+            Ok(UpdatedBoard {
+                board: board_with_tasks_inner(db, &board_id)?,
+                relocated,
+            })
+        })?;
+        log_activity(
+            &app,
+            "update",
+            "board",
+            &board_id,
+            json!({ "relocated": updated.relocated }),
+        );
+        Ok(updated)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn clear_vault_cache() {
+    clear_db_cache();
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub entity_type: String,
+    pub id: String,
+    pub problem: String,
+}
+
+fn integrity_issue(entity_type: &str, id: &str, problem: String) -> IntegrityIssue {
+    IntegrityIssue {
+        entity_type: entity_type.to_string(),
+        id: id.to_string(),
+        problem,
+    }
+}
+
+// Keeps every entry that parses so the remaining checks can still run over them.
+fn readable_entries<T: serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+    collection: &str,
+    entity_type: &str,
+    issues: &mut Vec<IntegrityIssue>,
+) -> Vec<T> {
+    let (parsed, failures) = parse_entries(value, collection);
+    issues.extend(failures.into_iter().map(|failure| {
+        let id = failure
+            .id
+            .unwrap_or_else(|| format!("{collection}[{}]", failure.index));
+        integrity_issue(entity_type, &id, format!("unreadable: {}", failure.error))
+    }));
+    parsed
+}
+
+fn validate_vault_inner(raw: &str) -> Vec<IntegrityIssue> {
+    let value: serde_json::Value = match serde_json::from_str(strip_bom(raw)) {
+        Ok(value) => value,
+        Err(e) => return vec![integrity_issue("vault", "", format!("unreadable: {e}"))],
+    };
+    let mut issues = Vec::new();
+    let db = Db {
+        boards: readable_entries(&value, "boards", "board", &mut issues),
+        tasks: readable_entries(&value, "tasks", "task", &mut issues),
+        projects: readable_entries(&value, "projects", "project", &mut issues),
+        epics: readable_entries(&value, "epics", "epic", &mut issues),
+        archive: readable_entries(&value, "archive", "archived_task", &mut issues),
+        ..default_db()
+    };
+    let projects = list_projects_inner(&db);
+    let epics = list_epics_inner(&db, None);
+    let project_exists = |id: &str| projects.iter().any(|p| p.id == id);
+
+    let live = list_tasks_inner(&db, None)
+        .into_iter()
+        .map(|task| ("task", task));
+    let archived = list_archived_tasks_inner(&db)
+        .into_iter()
+        .map(|task| ("archived_task", task));
+    for (entity_type, task) in live.chain(archived) {
+        match get_board_inner(&db, &task.board) {
+            Err(_) => issues.push(integrity_issue(
+                entity_type,
+                &task.id,
+                format!("board not found: {}", task.board),
+            )),
+            // Unarchiving falls back to the board's first column, so only live cards need theirs.
+            Ok(board) if entity_type == "task" && !board.columns.contains(&task.column) => issues
+                .push(integrity_issue(
+                    entity_type,
+                    &task.id,
+                    format!("column not on board {}: {}", board.id, task.column),
+                )),
+            Ok(_) => {}
+        }
+        if let Some(project_id) = task.project_id.as_deref().filter(|id| !project_exists(id)) {
+            issues.push(integrity_issue(
+                entity_type,
+                &task.id,
+                format!("project not found: {project_id}"),
+            ));
+        }
+        if let Some(epic_id) = task
+            .epic_id
+            .as_deref()
+            .filter(|id| !epics.iter().any(|e| e.id == *id))
+        {
+            issues.push(integrity_issue(
+                entity_type,
+                &task.id,
+                format!("epic not found: {epic_id}"),
+            ));
+        }
+    }
+    for epic in &epics {
+        if let Some(project_id) = epic.project_id.as_deref().filter(|id| !project_exists(id)) {
+            issues.push(integrity_issue(
+                "epic",
+                &epic.id,
+                format!("project not found: {project_id}"),
+            ));
+        }
+    }
+    issues
+}
+
+#[tauri::command]
+fn validate_vault(app: AppHandle) -> std::result::Result<Vec<IntegrityIssue>, ErrorResponse> {
+    (|| -> Result<Vec<IntegrityIssue>> {
+        let path = db_path(&app)?;
+        ensure_db(&path)?;
+        let raw = fs::read_to_string(&path)?;
+        Ok(validate_vault_inner(&raw))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairTaskColumnsPayload {
+    pub board_id: Option<String>,
+    pub fallback_column: Option<String>,
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairedTask {
+    pub task_id: String,
+    pub board: String,
+    pub from: String,
+    pub to: String,
+}
+
+fn repair_task_columns_inner(
+    db: &mut Db,
+    payload: &RepairTaskColumnsPayload,
+) -> Result<Vec<RepairedTask>> {
+    if let Some(board_id) = &payload.board_id {
+        if !db.boards.iter().any(|b| &b.id == board_id) {
+            return Err(VaultError::BoardNotFound(board_id.clone()));
+        }
+    }
+
+    let mut repairs = Vec::new();
+    // Tasks whose board is missing have no columns to repair against; validate_vault reports them.
+    for task in &db.tasks {
+        if payload
+            .board_id
+            .as_ref()
+            .is_some_and(|id| &task.board != id)
+        {
+            continue;
+        }
+        let Some(board) = db.boards.iter().find(|b| b.id == task.board) else {
+            continue;
+        };
+        if board.columns.contains(&task.column) {
+            continue;
+        }
+        let to = match &payload.fallback_column {
+            Some(column) if board.columns.contains(column) => column.clone(),
+            Some(column) => {
+                return Err(VaultError::ColumnNotFound(board.id.clone(), column.clone()))
+            }
+            None => board.columns.first().cloned().ok_or_else(|| {
+                VaultError::InvalidData(format!("board {} has no columns", board.id))
+            })?,
+        };
+        repairs.push(RepairedTask {
+            task_id: task.id.clone(),
+            board: board.id.clone(),
+            from: task.column.clone(),
+            to,
+        });
+    }
+
+    if !payload.dry_run.unwrap_or(false) {
+        let now = now_iso();
+        for repair in &repairs {
+            if let Some(task) = db.tasks.iter_mut().find(|t| t.id == repair.task_id) {
+                set_task_column(task, &repair.to, &now);
+            }
+        }
+    }
+    Ok(repairs)
+}
+
+#[tauri::command]
+fn repair_task_columns(
+    app: AppHandle,
+    payload: RepairTaskColumnsPayload,
+) -> std::result::Result<Vec<RepairedTask>, ErrorResponse> {
+    (|| -> Result<Vec<RepairedTask>> {
+        let dry_run = payload.dry_run.unwrap_or(false);
+        let repairs = with_locked_db_if(&db_path(&app)?, |db| {
+            let repairs = repair_task_columns_inner(db, &payload)?;
+            let changed = !dry_run && !repairs.is_empty();
+            Ok((repairs, changed))
+        })?;
+        if !dry_run && !repairs.is_empty() {
+            log_activity(
+                &app,
+                "repair_columns",
+                "vault",
+                "",
+                json!({ "repairs": repairs }),
+            );
+        }
+        Ok(repairs)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const UNGROUPED_LANE: &str = "Ungrouped";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Swimlane {
+    pub lane: String,
+    pub columns: Vec<BoardColumn>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSwimlanes {
+    pub board: Board,
+    pub group_by: String,
+    pub lanes: Vec<Swimlane>,
+}
+
+// Looks the field up on the serialized task, so any stored field works by either spelling;
+// list fields such as `tags` put the task in one lane per value.
+fn lane_keys(task: &Task, group_by: &str) -> Vec<String> {
+    let value = serde_json::to_value(task).unwrap_or_default();
+    let snake = group_by.to_string();
+    let camel = snake_to_camel(group_by);
+    let field = value.get(&snake).or_else(|| value.get(&camel));
+    let scalar = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => non_empty(s.trim().to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    };
+    let keys = match field {
+        Some(serde_json::Value::Array(items)) => items.iter().filter_map(scalar).collect(),
+        Some(v) => scalar(v).into_iter().collect(),
+        None => Vec::new(),
+    };
+    if keys.is_empty() {
+        vec![UNGROUPED_LANE.to_string()]
+    } else {
+        keys
+    }
+}
+
+fn snake_to_camel(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut upper = false;
+    for c in raw.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn board_swimlanes_inner(db: &Db, board_id: &str, group_by: &str) -> Result<BoardSwimlanes> {
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .cloned()
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+
+    let mut lanes: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+    for task in db.tasks.iter().filter(|t| t.board == board_id) {
+        for key in lane_keys(task, group_by) {
+            lanes.entry(key).or_default().push(task);
+        }
+    }
+    // Named lanes sort alphabetically, with the catch-all lane last.
+    let ungrouped = lanes.remove(UNGROUPED_LANE);
+    let lanes = lanes
+        .into_iter()
+        .chain(ungrouped.map(|tasks| (UNGROUPED_LANE.to_string(), tasks)))
+        .map(|(lane, tasks)| Swimlane {
+            columns: bucket_into_columns(&board, tasks.into_iter(), TaskSort::Order),
+            lane,
+        })
+        .collect();
+
+    Ok(BoardSwimlanes {
+        board,
+        group_by: group_by.to_string(),
+        lanes,
+    })
+}
+
+#[tauri::command]
+fn get_board_swimlanes(
+    app: AppHandle,
+    board_id: String,
+    group_by: String,
+) -> std::result::Result<BoardSwimlanes, ErrorResponse> {
+    (|| -> Result<BoardSwimlanes> {
+        let db = load_db(&db_path(&app)?)?;
+        board_swimlanes_inner(&db, &board_id, &group_by)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn set_task_priority_inner(db: &mut Db, task_id: &str, priority: Option<Priority>) -> Result<Task> {
+    if let Some(Priority::Other(raw)) = &priority {
+        return Err(VaultError::InvalidData(format!(
+            "unknown priority {raw}; use low, medium, high or urgent"
+        )));
+    }
+    edit_task_inner(db, task_id, |task| {
+        task.priority = priority;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn set_task_priority(
+    app: AppHandle,
+    task_id: String,
+    priority: Option<Priority>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            set_task_priority_inner(db, &task_id, priority)
+        })?;
+        log_activity(
+            &app,
+            "set_priority",
+            "task",
+            &task.id,
+            json!({ "priority": task.priority }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn validate_story_payload(db: &Db, payload: &CreateStoryPayload) -> Result<()> {
+    if payload.title.trim().is_empty() {
+        return Err(VaultError::InvalidData("title cannot be empty".to_string()));
+    }
+    let board_id = payload.board_id.as_deref().unwrap_or("default");
+    let board = db
+        .boards
+        .iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))?;
+    if let Some(column) = &payload.column {
+        if !board.columns.contains(column) {
+            return Err(VaultError::ColumnNotFound(
+                board_id.to_string(),
+                column.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Every payload is checked before any is created, so a bad row leaves the vault untouched.
+fn create_stories_inner(
+    db: &mut Db,
+    payloads: Vec<CreateStoryPayload>,
+    slug_ids: bool,
+) -> Result<Vec<Task>> {
+    let problems = payloads
+        .iter()
+        .enumerate()
+        .filter_map(|(index, payload)| {
+            validate_story_payload(db, payload)
+                .err()
+                .map(|e| format!("#{}: {e}", index + 1))
+        })
+        .collect::<Vec<_>>();
+    if !problems.is_empty() {
+        return Err(VaultError::InvalidData(format!(
+            "no stories created; {}",
+            problems.join("; ")
+        )));
+    }
+    payloads
+        .into_iter()
+        .map(|payload| create_story_inner(db, payload, slug_ids))
+        .collect()
+}
+
+#[tauri::command]
+fn create_stories(
+    app: AppHandle,
+    payloads: Vec<CreateStoryPayload>,
+) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let slug_ids = slug_ids_enabled(&app)?;
+        let created = with_locked_db_if(&db_path(&app)?, |db| {
+            let created = create_stories_inner(db, payloads, slug_ids)?;
+            let changed = !created.is_empty();
+            Ok((created, changed))
+        })?;
+        if !created.is_empty() {
+            for task in &created {
+                log_activity(
+                    &app,
+                    "create",
+                    "task",
+                    &task.id,
+                    json!({ "title": task.title, "board": task.board, "column": task.column }),
+                );
+            }
+        }
+        Ok(created)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvRowError {
+    /// 1-based line in the CSV text, counting the header as line 1.
+    pub line: u64,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvImportSummary {
+    pub created: Vec<String>,
+    pub errors: Vec<CsvRowError>,
+}
+
+struct CsvStory {
+    payload: CreateStoryPayload,
+    tags: Vec<String>,
+    due: Option<String>,
+}
+
+fn csv_story(
+    db: &Db,
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    board_id: &str,
+) -> Result<CsvStory> {
+    let field = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let payload = CreateStoryPayload {
+        title: field("title").unwrap_or_default(),
+        column: field("column"),
+        description: field("description"),
+        board_id: Some(board_id.to_string()),
+        ..CreateStoryPayload::default()
+    };
+    validate_story_payload(db, &payload)?;
+    let tags = field("tags")
+        .map(|raw| {
+            raw.split(';')
+                .filter_map(|tag| normalize_tag(tag).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(CsvStory {
+        payload,
+        tags,
+        due: normalize_due(field("due").as_deref())?,
+    })
+}
+
+// Good rows are created even when others fail; each failure is reported with its line.
+fn import_stories_csv_inner(
+    db: &mut Db,
+    csv_text: &str,
+    board_id: &str,
+    slug_ids: bool,
+) -> Result<CsvImportSummary> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(csv_text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| VaultError::InvalidData(format!("unreadable CSV header: {e}")))?
+        .clone();
+    if !headers
+        .iter()
+        .any(|h| h.trim().eq_ignore_ascii_case("title"))
+    {
+        return Err(VaultError::InvalidData(
+            "CSV needs a title column".to_string(),
+        ));
+    }
+
+    let mut summary = CsvImportSummary::default();
+    for record in reader.records() {
+        let (line, story) = match record {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or_default();
+                (line, csv_story(db, &headers, &record, board_id))
+            }
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+                (line, Err(VaultError::InvalidData(e.to_string())))
+            }
+        };
+        match story.and_then(|story| {
+            let mut task = create_story_inner(db, story.payload, slug_ids)?;
+            task.tags.extend(story.tags);
+            dedupe_tags(&mut task.tags, true);
+            task.due = story.due;
+            if let Some(stored) = db.tasks.iter_mut().find(|t| t.id == task.id) {
+                *stored = task.clone();
+            }
+            Ok(task)
+        }) {
+            Ok(task) => summary.created.push(task.id),
+            Err(e) => summary.errors.push(CsvRowError {
+                line,
+                error: e.to_string(),
+            }),
+        }
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+fn import_stories_csv(
+    app: AppHandle,
+    csv_text: String,
+    board_id: Option<String>,
+) -> std::result::Result<CsvImportSummary, ErrorResponse> {
+    (|| -> Result<CsvImportSummary> {
+        let board_id = board_id.unwrap_or_else(|| "default".to_string());
+        let slug_ids = slug_ids_enabled(&app)?;
+        let summary = with_locked_db_if(&db_path(&app)?, |db| {
+            let summary = import_stories_csv_inner(db, &csv_text, &board_id, slug_ids)?;
+            let changed = !summary.created.is_empty();
+            Ok((summary, changed))
+        })?;
+        if !summary.created.is_empty() {
+            log_activity(
+                &app,
+                "import_csv",
+                "board",
+                &board_id,
+                json!({ "created": summary.created, "errors": summary.errors.len() }),
+            );
+        }
+        Ok(summary)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn escape_ics_text(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// RFC 5545 limits content lines to 75 octets; longer ones continue on lines starting with a space.
+fn fold_ics_line(line: &str) -> String {
+    const LIMIT: usize = 75;
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn render_due_ics(tasks: &[Task], stamp: chrono::DateTime<chrono::Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//kanban-vault//due dates//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for task in tasks {
+        let Some(due) = task.due.as_deref().and_then(parse_due) else {
+            continue;
+        };
+        let end = due.succ_opt().unwrap_or(due);
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@kanban-vault", escape_ics_text(&task.id)),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            format!("SUMMARY:{}", escape_ics_text(&task.title)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_ics_line(line)).collect()
+}
+
+#[tauri::command]
+fn export_due_ics(
+    app: AppHandle,
+    board_id: Option<String>,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let db = load_db(&db_path(&app)?)?;
+        let tasks = list_tasks_inner(&db, board_id.as_deref());
+        Ok(render_due_ics(&tasks, chrono::Utc::now()))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<std::process::Output> {
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| VaultError::Git(format!("could not run git: {e}")))
+}
+
+fn git_stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
+
+/// Commits everything under `dir` and returns the new commit hash. Paths are limited to
+/// the vault so a vault nested inside a larger repo doesn't sweep up unrelated changes.
+fn git_commit_in(dir: &Path, message: &str) -> Result<String> {
+    let probe = run_git(dir, &["rev-parse", "--is-inside-work-tree"])?;
+    if !probe.status.success() {
+        return Err(VaultError::Git(format!(
+            "{} is not inside a git repository",
+            dir.display()
+        )));
+    }
+    let add = run_git(dir, &["add", "-A", "--", "."])?;
+    if !add.status.success() {
+        return Err(VaultError::Git(git_stderr(&add)));
+    }
+    let status = run_git(dir, &["status", "--porcelain", "--", "."])?;
+    if status.stdout.iter().all(|b| b.is_ascii_whitespace()) {
+        return Err(VaultError::Git("nothing to commit".to_string()));
+    }
+    let commit = run_git(dir, &["commit", "-q", "-m", message, "--", "."])?;
+    if !commit.status.success() {
+        return Err(VaultError::Git(git_stderr(&commit)));
+    }
+    let head = run_git(dir, &["rev-parse", "HEAD"])?;
+    if !head.status.success() {
+        return Err(VaultError::Git(git_stderr(&head)));
+    }
+    Ok(String::from_utf8_lossy(&head.stdout).trim().to_string())
+}
+
+const AUTO_COMMIT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Activity waiting for an auto-commit, per vault directory; an entry exists while its worker runs.
+static PENDING_COMMITS: Mutex<BTreeMap<PathBuf, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+fn auto_commit_message(messages: &[String]) -> String {
+    match messages {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, rest @ ..] => format!("{first} (+{} more)\n\n{}", rest.len(), messages.join("\n")),
+    }
+}
+
+// Batch commands log once per task, so messages arriving within the debounce share one
+// commit. Git runs on a worker thread, one per vault, so a slow repo never holds up a command.
+fn queue_auto_commit(dir: &Path, message: String) {
+    let Ok(mut pending) = PENDING_COMMITS.lock() else {
+        return;
+    };
+    if let Some(messages) = pending.get_mut(dir) {
+        messages.push(message);
+        return;
+    }
+    pending.insert(dir.to_path_buf(), vec![message]);
+    drop(pending);
+
+    let dir = dir.to_path_buf();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(AUTO_COMMIT_DEBOUNCE);
+        let Ok(mut pending) = PENDING_COMMITS.lock() else {
+            return;
+        };
+        let messages = match pending.get_mut(&dir) {
+            Some(messages) if !messages.is_empty() => std::mem::take(messages),
+            _ => {
+                pending.remove(&dir);
+                return;
+            }
+        };
+        drop(pending);
+        // Commit failures never block the change itself.
+        let _ = git_commit_in(&dir, &auto_commit_message(&messages));
+    });
+}
+
+#[tauri::command]
+fn git_commit_vault(
+    app: AppHandle,
+    message: Option<String>,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let dir = vault_dir(&app)?;
+        let message = message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| format!("Vault snapshot {}", now_iso()));
+        git_commit_in(&dir, &message)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recurrence {
+    Daily,
+    Weekly(chrono::Weekday),
+    /// Clamped to the last day in shorter months, so `monthly:31` still fires every month.
+    Monthly(u32),
+}
+
+fn parse_recurrence(raw: &str) -> Result<Recurrence> {
+    let raw = raw.trim().to_lowercase();
+    let invalid = || {
+        VaultError::InvalidData(format!(
+            "unsupported recurrence {raw:?}; use daily, weekly:<day> or monthly:<1-31>"
+        ))
+    };
+    let (kind, arg) = match raw.split_once(':') {
+        Some((kind, arg)) => (kind.trim(), Some(arg.trim())),
+        None => (raw.as_str(), None),
+    };
+    match (kind, arg) {
+        ("daily", None) => Ok(Recurrence::Daily),
+        ("weekly", Some(day)) => day
+            .parse::<chrono::Weekday>()
+            .map(Recurrence::Weekly)
+            .map_err(|_| invalid()),
+        ("monthly", Some(day)) => match day.parse::<u32>() {
+            Ok(day @ 1..=31) => Ok(Recurrence::Monthly(day)),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+fn clamped_day_of_month(year: i32, month: u32, day: u32) -> chrono::NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|d| chrono::NaiveDate::from_ymd_opt(year, month, d))
+        .expect("every month has a first day")
+}
+
+impl Recurrence {
+    /// The most recent occurrence on or before `day`; this is the period a fresh instance covers.
+    fn latest_occurrence(self, day: chrono::NaiveDate) -> chrono::NaiveDate {
+        use chrono::Datelike;
+        match self {
+            Recurrence::Daily => day,
+            Recurrence::Weekly(weekday) => {
+                let back =
+                    (day.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+                day - chrono::Duration::days(back as i64)
+            }
+            Recurrence::Monthly(n) => {
+                let this_month = clamped_day_of_month(day.year(), day.month(), n);
+                if this_month <= day {
+                    return this_month;
+                }
+                let (year, month) = if day.month() == 1 {
+                    (day.year() - 1, 12)
+                } else {
+                    (day.year(), day.month() - 1)
+                };
+                clamped_day_of_month(year, month, n)
+            }
+        }
+    }
+}
+
+fn set_task_recurrence_inner(
+    db: &mut Db,
+    task_id: &str,
+    recurrence: Option<String>,
+) -> Result<Task> {
+    let recurrence = match recurrence.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(raw) => {
+            parse_recurrence(raw)?;
+            Some(raw.to_lowercase())
+        }
+    };
+    edit_task_inner(db, task_id, |task| {
+        task.recurrence = recurrence;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn set_task_recurrence(
+    app: AppHandle,
+    task_id: String,
+    recurrence: Option<String>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            set_task_recurrence_inner(db, &task_id, recurrence)
+        })?;
+        log_activity(
+            &app,
+            "set_recurrence",
+            "task",
+            &task.id,
+            json!({ "recurrence": task.recurrence }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn parse_materialize_day(raw: Option<&str>) -> Result<chrono::NaiveDate> {
+    match raw.map(str::trim).filter(|r| !r.is_empty()) {
+        None => Ok(today()),
+        Some(raw) => parse_day(raw).or_else(|e| {
+            parse_instant(raw)
+                .map(|at| at.with_timezone(&chrono::Local).date_naive())
+                .ok_or(e)
+        }),
+    }
+}
+
+/// Instances remember their template and occurrence date, so running this again in the
+/// same period (even after the instance was moved, archived or renamed) creates nothing.
+fn materialize_recurring_inner(
+    db: &mut Db,
+    day: chrono::NaiveDate,
+    slug_ids: bool,
+) -> Result<Vec<Task>> {
+    let templates = db
+        .tasks
+        .iter()
+        .filter(|t| t.archived.is_none())
+        .filter_map(|t| {
+            let recurrence = parse_recurrence(t.recurrence.as_deref()?).ok()?;
+            Some((t.clone(), recurrence.latest_occurrence(day)))
+        })
+        .collect::<Vec<_>>();
+
+    let mut created = Vec::new();
+    for (template, occurrence) in templates {
+        let starts = template
+            .created
+            .as_deref()
+            .and_then(parse_instant)
+            .map(|at| at.with_timezone(&chrono::Local).date_naive());
+        if starts.is_some_and(|starts| occurrence < starts) {
+            continue;
+        }
+        let date = occurrence.format("%Y-%m-%d").to_string();
+        let exists = db.tasks.iter().chain(db.archive.iter()).any(|t| {
+            t.recurrence_of.as_deref() == Some(template.id.as_str())
+                && t.recurrence_date.as_deref() == Some(date.as_str())
+        });
+        if exists {
+            continue;
+        }
+        let Some(column) = db
+            .boards
+            .iter()
+            .find(|b| b.id == template.board)
+            .and_then(|b| b.columns.first())
+        else {
+            continue;
+        };
+
+        let mut instance = template.clone();
+        instance.id = new_entity_id(db, "story", &template.title, slug_ids);
+        instance.column = column.clone();
+        instance.due = Some(date.clone());
+        instance.created = Some(now_iso());
+        instance.updated = None;
+        instance.order = None;
+        instance.transitions = vec![];
+        instance.blocked_by = vec![];
+        instance.attachments = vec![];
+        for item in &mut instance.checklist {
+            item.done = false;
+        }
+        instance.recurrence = None;
+        instance.recurrence_of = Some(template.id.clone());
+        instance.recurrence_date = Some(date);
+        db.tasks.push(instance.clone());
+        created.push(instance);
+    }
+    Ok(created)
+}
+
+#[tauri::command]
+fn materialize_recurring_tasks(
+    app: AppHandle,
+    now: Option<String>,
+) -> std::result::Result<Vec<Task>, ErrorResponse> {
+    (|| -> Result<Vec<Task>> {
+        let day = parse_materialize_day(now.as_deref())?;
+        let slug_ids = slug_ids_enabled(&app)?;
+        let created = with_locked_db_if(&db_path(&app)?, |db| {
+            let created = materialize_recurring_inner(db, day, slug_ids)?;
+            let changed = !created.is_empty();
+            Ok((created, changed))
+        })?;
+        for task in &created {
+            log_activity(
+                &app,
+                "create",
+                "task",
+                &task.id,
+                json!({ "recurrenceOf": task.recurrence_of, "recurrenceDate": task.recurrence_date }),
+            );
+        }
+        Ok(created)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn link_task_to_epic_inner(db: &mut Db, task_id: &str, epic_id: &str) -> Result<Task> {
+    if !list_epics_inner(db, None).iter().any(|e| e.id == epic_id) {
+        return Err(VaultError::EpicNotFound(epic_id.to_string()));
+    }
+    edit_task_inner(db, task_id, |task| {
+        task.epic_id = Some(epic_id.to_string());
+        Ok(())
+    })
+}
+
+fn unlink_task_from_epic_inner(db: &mut Db, task_id: &str) -> Result<Task> {
+    edit_task_inner(db, task_id, |task| {
+        task.epic_id = None;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn link_task_to_epic(
+    app: AppHandle,
+    task_id: String,
+    epic_id: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            link_task_to_epic_inner(db, &task_id, &epic_id)
+        })?;
+        log_activity(
+            &app,
+            "link_epic",
+            "task",
+            &task.id,
+            json!({ "epicId": epic_id }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn unlink_task_from_epic(
+    app: AppHandle,
+    task_id: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let (task, previous) = with_locked_db(&db_path(&app)?, |db| {
+            let previous = db
+                .tasks
+                .iter()
+                .find(|t| t.id == task_id)
+                .and_then(|t| t.epic_id.clone());
+            Ok((unlink_task_from_epic_inner(db, &task_id)?, previous))
+        })?;
+        log_activity(
+            &app,
+            "unlink_epic",
+            "task",
+            &task.id,
+            json!({ "epicId": previous }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicNode {
+    #[serde(flatten)]
+    pub epic: Epic,
+    pub stories: Vec<Task>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectNode {
+    #[serde(flatten)]
+    pub project: Project,
+    pub epics: Vec<EpicNode>,
+    /// Stories that name this project but no (existing) epic.
+    pub stories: Vec<Task>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTree {
+    pub projects: Vec<ProjectNode>,
+    /// Epics whose project is unset or missing, with their stories.
+    pub unassigned_epics: Vec<EpicNode>,
+    /// Stories that reach neither an existing epic nor an existing project.
+    pub orphans: Vec<Task>,
+}
+
+// A story sits under its epic when that epic exists, otherwise under its project, otherwise
+// with the orphans; so every task appears exactly once.
+fn project_tree_inner(db: &Db) -> ProjectTree {
+    let projects = list_projects_inner(db);
+    let epics = list_epics_inner(db, None);
+    let project_ids = projects
+        .iter()
+        .map(|p| p.id.as_str())
+        .collect::<BTreeSet<_>>();
+    let epic_ids = epics.iter().map(|e| e.id.as_str()).collect::<BTreeSet<_>>();
+
+    let mut by_epic: HashMap<String, Vec<Task>> = HashMap::new();
+    let mut by_project: HashMap<String, Vec<Task>> = HashMap::new();
+    let mut orphans = Vec::new();
+    for task in list_tasks_inner(db, None) {
+        if let Some(epic) = task
+            .epic_id
+            .clone()
+            .filter(|e| epic_ids.contains(e.as_str()))
+        {
+            by_epic.entry(epic).or_default().push(task);
+        } else if let Some(project) = task
+            .project_id
+            .clone()
+            .filter(|p| project_ids.contains(p.as_str()))
+        {
+            by_project.entry(project).or_default().push(task);
+        } else {
+            orphans.push(task);
+        }
+    }
+
+    let mut epics_by_project: HashMap<String, Vec<EpicNode>> = HashMap::new();
+    let mut unassigned_epics = Vec::new();
+    for epic in epics {
+        let node = EpicNode {
+            stories: by_epic.remove(&epic.id).unwrap_or_default(),
+            epic,
+        };
+        match node
+            .epic
+            .project_id
+            .clone()
+            .filter(|p| project_ids.contains(p.as_str()))
+        {
+            Some(project) => epics_by_project.entry(project).or_default().push(node),
+            None => unassigned_epics.push(node),
+        }
+    }
+
+    let projects = projects
+        .into_iter()
+        .map(|project| ProjectNode {
+            epics: epics_by_project.remove(&project.id).unwrap_or_default(),
+            stories: by_project.remove(&project.id).unwrap_or_default(),
+            project,
+        })
+        .collect();
+    ProjectTree {
+        projects,
+        unassigned_epics,
+        orphans,
+    }
+}
+
+#[tauri::command]
+fn get_project_tree(app: AppHandle) -> std::result::Result<ProjectTree, ErrorResponse> {
+    (|| -> Result<ProjectTree> {
+        let db = load_db(&db_path(&app)?)?;
+        Ok(project_tree_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+// Same bucketing as the board view, so the counts always match what the board shows.
+fn column_counts_inner(db: &Db, board_id: &str) -> Result<BTreeMap<String, usize>> {
+    let board = board_with_tasks_inner(db, board_id)?;
+    Ok(board
+        .columns
+        .into_iter()
+        .map(|c| (c.name, c.tasks.len()))
+        .collect())
+}
+
+#[tauri::command]
+fn count_tasks_by_column(
+    app: AppHandle,
+    board_id: String,
+) -> std::result::Result<BTreeMap<String, usize>, ErrorResponse> {
+    (|| -> Result<BTreeMap<String, usize>> {
+        let db = load_db(&db_path(&app)?)?;
+        column_counts_inner(&db, &board_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStats {
+    pub boards: usize,
+    pub tasks: usize,
+    pub projects: usize,
+    pub epics: usize,
+    /// Keyed by board id; boards without tasks count 0.
+    pub tasks_per_board: BTreeMap<String, usize>,
+    /// Column names summed across boards; every board column appears, even when empty.
+    pub tasks_per_column: BTreeMap<String, usize>,
+    /// Past-due tasks that aren't in one of their board's done columns.
+    pub overdue: usize,
+}
+
+fn vault_stats_inner(db: &Db) -> VaultStats {
+    let boards = list_boards_inner(db);
+    let tasks = list_tasks_inner(db, None);
+    let mut tasks_per_board = boards
+        .iter()
+        .map(|b| (b.id.clone(), 0))
+        .collect::<BTreeMap<_, _>>();
+    let mut tasks_per_column = boards
+        .iter()
+        .flat_map(|b| b.columns.iter().map(|c| (c.clone(), 0)))
+        .collect::<BTreeMap<_, _>>();
+    let mut overdue = 0;
+    for task in &tasks {
+        *tasks_per_board.entry(task.board.clone()).or_default() += 1;
+        *tasks_per_column.entry(task.column.clone()).or_default() += 1;
+        if task.due_status == Some(DueStatus::Overdue)
+            && !done_columns_of(db, &task.board).contains(&task.column)
+        {
+            overdue += 1;
+        }
+    }
+    VaultStats {
+        boards: boards.len(),
+        tasks: tasks.len(),
+        projects: list_projects_inner(db).len(),
+        epics: list_epics_inner(db, None).len(),
+        tasks_per_board,
+        tasks_per_column,
+        overdue,
+    }
+}
+
+#[tauri::command]
+fn vault_stats(app: AppHandle) -> std::result::Result<VaultStats, ErrorResponse> {
+    (|| -> Result<VaultStats> {
+        let db = load_db(&db_path(&app)?)?;
+        Ok(vault_stats_inner(&db))
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const ATTACHMENTS_DIR_NAME: &str = "attachments";
+
+fn attachments_dir(vault: &Path, task_id: &str) -> Result<PathBuf> {
+    Ok(vault.join(ATTACHMENTS_DIR_NAME).join(sanitize_id(task_id)?))
+}
+
+// Only the last path component is kept, so `../../etc/passwd` becomes `passwd`.
+fn sanitize_attachment_name(name: &str) -> Result<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or("").trim();
+    if base.is_empty() || base == "." || base == ".." {
+        return Err(VaultError::InvalidData(format!(
+            "not a usable file name: {name:?}"
+        )));
+    }
+    if base.starts_with('.') || base.chars().any(char::is_control) {
+        return Err(VaultError::InvalidData(format!(
+            "attachment names cannot start with a dot or contain control characters: {name:?}"
+        )));
+    }
+    Ok(base.to_string())
+}
+
+/// `report.pdf` becomes `report-2.pdf`, `report-3.pdf`, … until the name is free.
+fn unique_attachment_name(dir: &Path, taken: &[String], name: &str) -> String {
+    let is_free =
+        |candidate: &str| !taken.iter().any(|t| t == candidate) && !dir.join(candidate).exists();
+    if is_free(name) {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem}-{n}{ext}"))
+        .find(|candidate| is_free(candidate))
+        .expect("some suffix is free")
+}
+
+fn attach_file_inner(
+    db: &mut Db,
+    vault: &Path,
+    task_id: &str,
+    source: &Path,
+    file_name: &str,
+) -> Result<Task> {
+    let name = sanitize_attachment_name(file_name)?;
+    let dir = attachments_dir(vault, task_id)?;
+    let taken = db
+        .tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?
+        .attachments
+        .clone();
+    if !source.is_file() {
+        return Err(VaultError::InvalidData(format!(
+            "not a file: {}",
+            source.display()
+        )));
+    }
+    fs::create_dir_all(&dir)?;
+    let name = unique_attachment_name(&dir, &taken, &name);
+    fs::copy(source, dir.join(&name))?;
+    edit_task_inner(db, task_id, |task| {
+        task.attachments.push(name);
+        Ok(())
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAttachment {
+    pub name: String,
+    pub path: String,
+    /// False when the file was removed from disk behind the app's back.
+    pub exists: bool,
+}
+
+fn list_attachments_inner(db: &Db, vault: &Path, task_id: &str) -> Result<Vec<TaskAttachment>> {
+    let task = db
+        .tasks
+        .iter()
+        .chain(&db.archive)
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| VaultError::TaskNotFound(task_id.to_string()))?;
+    let dir = attachments_dir(vault, task_id)?;
+    Ok(task
+        .attachments
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            TaskAttachment {
+                name: name.clone(),
+                exists: path.is_file(),
+                path: path.to_string_lossy().to_string(),
+            }
+        })
+        .collect())
+}
+
+fn remove_attachment_inner(db: &mut Db, vault: &Path, task_id: &str, name: &str) -> Result<Task> {
+    // The stored list can be hand-edited, so a name that isn't a plain file name is never
+    // joined onto the attachments directory.
+    if sanitize_attachment_name(name)? != name {
+        return Err(VaultError::InvalidData(format!(
+            "not a plain attachment name: {name:?}"
+        )));
+    }
+    let dir = attachments_dir(vault, task_id)?;
+    let task = edit_task_inner(db, task_id, |task| {
+        let before = task.attachments.len();
+        task.attachments.retain(|a| a != name);
+        if task.attachments.len() == before {
+            return Err(VaultError::InvalidData(format!(
+                "task {task_id} has no attachment named {name:?}"
+            )));
+        }
+        Ok(())
+    })?;
+    match fs::remove_file(dir.join(name)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    if task.attachments.is_empty() {
+        let _ = fs::remove_dir(&dir);
+    }
+    Ok(task)
+}
+
+#[tauri::command]
+fn attach_file(
+    app: AppHandle,
+    task_id: String,
+    source_path: String,
+    file_name: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let vault = vault_dir(&app)?;
+        let path = db_path(&app)?;
+        // Locked by hand rather than via `with_locked_db` to clean up the copy on a failed save.
+        let _lock = lock_db_file(&path)?;
+        let mut db = load_db(&path)?;
+        let task = attach_file_inner(
+            &mut db,
+            &vault,
+            &task_id,
+            Path::new(&source_path),
+            &file_name,
+        )?;
+        if let Err(e) = save_db(&path, &db) {
+            // Nothing references the copy once the save fails, so don't leave it behind.
+            if let Some(name) = task.attachments.last() {
+                let dir = attachments_dir(&vault, &task.id)?;
+                let _ = fs::remove_file(dir.join(name));
+                let _ = fs::remove_dir(&dir);
+            }
+            return Err(e);
+        }
+        forget_undo_history(&path);
+        log_activity(
+            &app,
+            "attach",
+            "task",
+            &task.id,
+            json!({ "name": task.attachments.last() }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn list_attachments(
+    app: AppHandle,
+    task_id: String,
+) -> std::result::Result<Vec<TaskAttachment>, ErrorResponse> {
+    (|| -> Result<Vec<TaskAttachment>> {
+        let db = load_db(&db_path(&app)?)?;
+        list_attachments_inner(&db, &vault_dir(&app)?, &task_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn remove_attachment(
+    app: AppHandle,
+    task_id: String,
+    name: String,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let vault = vault_dir(&app)?;
+        let path = db_path(&app)?;
+        let task = with_locked_db(&path, |db| {
+            remove_attachment_inner(db, &vault, &task_id, &name)
+        })?;
+        forget_undo_history(&path);
+        log_activity(
+            &app,
+            "remove_attachment",
+            "task",
+            &task.id,
+            json!({ "name": name }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const UNDO_JOURNAL_LIMIT: usize = 50;
+const UNDO_ACTION: &str = "undo";
+
+/// What `undo_last_change` reverted; the labels come from the activity log entry of the
+/// command that made the change, and are missing if that command didn't log one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoneChange {
+    pub at: String,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    seq: u64,
+    before: String,
+    after: String,
+    change: UndoneChange,
+}
+
+// Session-only: per database file, the last few writes with the bytes they replaced.
+static UNDO_JOURNAL: Mutex<BTreeMap<PathBuf, VecDeque<JournalEntry>>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// The journal entry written by the command running on this thread, until it is labelled.
+    /// Taking the vault lock starts a new command and clears it, so `log_activity` only ever
+    /// labels the save made by its own command.
+    static UNLABELLED_CHANGE: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+fn journal_change(path: &Path, before: String, after: String) {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static JOURNAL_SEQ: AtomicU64 = AtomicU64::new(0);
+    UNLABELLED_CHANGE.set(None);
+    if before == after {
+        return;
+    }
+    if let Ok(mut journal) = UNDO_JOURNAL.lock() {
+        let seq = JOURNAL_SEQ.fetch_add(1, Ordering::Relaxed);
+        UNLABELLED_CHANGE.set(Some(seq));
+        let entries = journal.entry(path.to_path_buf()).or_default();
+        entries.push_back(JournalEntry {
+            seq,
+            before,
+            after,
+            change: UndoneChange {
+                at: now_iso(),
+                action: None,
+                entity_type: None,
+                id: None,
+            },
+        });
+        if entries.len() > UNDO_JOURNAL_LIMIT {
+            entries.pop_front();
+        }
+    }
+}
+
+fn label_change(path: &Path, action: &str, entity_type: &str, id: &str) {
+    let Some(seq) = UNLABELLED_CHANGE.take() else {
+        return;
+    };
+    let Ok(mut journal) = UNDO_JOURNAL.lock() else {
+        return;
+    };
+    let Some(entry) = journal
+        .get_mut(path)
+        .and_then(|entries| entries.iter_mut().find(|e| e.seq == seq))
+    else {
+        return;
+    };
+    entry.change.action = Some(action.to_string());
+    entry.change.entity_type = Some(entity_type.to_string());
+    entry.change.id = Some(id.to_string());
+}
+
+// The journal only restores `pm-db.json`, so undoing across an attachment change would leave
+// `attachments/` out of sync with the cards. Such changes end the undo history instead.
+fn forget_undo_history(path: &Path) {
+    UNLABELLED_CHANGE.set(None);
+    if let Ok(mut journal) = UNDO_JOURNAL.lock() {
+        journal.remove(path);
+    }
+}
+
+/// Puts back the bytes the most recent journaled write replaced. Refuses when the file no
+/// longer holds what that write produced, so edits made outside the app are never lost.
+fn undo_last_change_inner(path: &Path) -> Result<UndoneChange> {
+    let _lock = lock_db_file(path)?;
+    let mut journal = UNDO_JOURNAL
+        .lock()
+        .map_err(|_| VaultError::InvalidData("undo journal is unavailable".to_string()))?;
+    let entries = journal.entry(path.to_path_buf()).or_default();
+    let Some(last) = entries.back() else {
+        return Err(VaultError::InvalidData("nothing to undo".to_string()));
+    };
+    if fs::read_to_string(path)? != last.after {
+        entries.clear();
+        return Err(VaultError::InvalidData(
+            "the vault changed outside the app since the last edit; undo history was cleared"
+                .to_string(),
+        ));
+    }
+    let db = parse_db(&last.before)?;
+    write_atomic(path, last.before.as_bytes())?;
+    let undone = last.change.clone();
+    entries.pop_back();
+    cache_db(path, &db)?;
+    Ok(undone)
+}
+
+#[tauri::command]
+fn undo_last_change(app: AppHandle) -> std::result::Result<UndoneChange, ErrorResponse> {
+    (|| -> Result<UndoneChange> {
+        let undone = undo_last_change_inner(&db_path(&app)?)?;
+        log_activity(
+            &app,
+            UNDO_ACTION,
+            undone.entity_type.as_deref().unwrap_or("vault"),
+            undone.id.as_deref().unwrap_or(""),
+            json!({ "action": undone.action, "at": undone.at }),
+        );
+        Ok(undone)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn get_board_inner(db: &Db, board_id: &str) -> Result<Board> {
+    list_boards_inner(db)
+        .into_iter()
+        .find(|b| b.id == board_id)
+        .ok_or_else(|| VaultError::BoardNotFound(board_id.to_string()))
+}
+
+#[tauri::command]
+fn get_board(app: AppHandle, board_id: String) -> std::result::Result<Board, ErrorResponse> {
+    (|| -> Result<Board> {
+        let db = load_db(&db_path(&app)?)?;
+        get_board_inner(&db, &board_id)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteEpicSummary {
+    pub epic_id: String,
+    pub reassigned_to: Option<String>,
+    pub tasks_updated: Vec<String>,
+}
+
+fn delete_epic_inner(
+    db: &mut Db,
+    epic_id: &str,
+    reassign_to: Option<&str>,
+) -> Result<DeleteEpicSummary> {
+    let index = db
+        .epics
+        .iter()
+        .position(|e| e.id == epic_id)
+        .ok_or_else(|| VaultError::EpicNotFound(epic_id.to_string()))?;
+    // Reassigned stories follow their new epic into its project.
+    let target_project = match reassign_to {
+        Some(target) if target == epic_id => {
+            return Err(VaultError::InvalidData(format!(
+                "cannot reassign stories of epic {epic_id} to itself"
+            )));
+        }
+        Some(target) => Some(
+            db.epics
+                .iter()
+                .find(|e| e.id == target)
+                .ok_or_else(|| VaultError::EpicNotFound(target.to_string()))?
+                .project_id
+                .clone(),
+        ),
+        None => None,
+    };
+
+    db.epics.remove(index);
+    let now = now_iso();
+    let mut tasks_updated = Vec::new();
+    for task in db.tasks.iter_mut().chain(db.archive.iter_mut()) {
+        if task.epic_id.as_deref() != Some(epic_id) {
+            continue;
+        }
+        task.epic_id = reassign_to.map(str::to_string);
+        if let Some(project_id) = &target_project {
+            task.project_id = project_id.clone();
+        }
+        task.updated = Some(now.clone());
+        tasks_updated.push(task.id.clone());
+    }
+
+    Ok(DeleteEpicSummary {
+        epic_id: epic_id.to_string(),
+        reassigned_to: reassign_to.map(str::to_string),
+        tasks_updated,
+    })
+}
+
+#[tauri::command]
+fn delete_epic(
+    app: AppHandle,
+    epic_id: String,
+    reassign_to: Option<String>,
+) -> std::result::Result<DeleteEpicSummary, ErrorResponse> {
+    (|| -> Result<DeleteEpicSummary> {
+        let summary = with_locked_db(&db_path(&app)?, |db| {
+            delete_epic_inner(db, &epic_id, reassign_to.as_deref())
+        })?;
+        log_activity(
+            &app,
+            "delete",
+            "epic",
+            &epic_id,
+            json!({ "summary": summary }),
+        );
+        Ok(summary)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveTaskWithPositionPayload {
+    pub task_id: String,
+    pub target_column: String,
+    /// Position among the column's cards after the move; past the end means last.
+    pub target_index: usize,
+    pub force: Option<bool>,
+}
+
+// Renumbers the whole column 0, 1, 2, … like `reorder_tasks`, so the drop position is exact
+// no matter how the existing `order` values were spread.
+fn move_task_with_position_inner(
+    db: &mut Db,
+    payload: &MoveTaskWithPositionPayload,
+) -> Result<Vec<String>> {
+    let moved = update_task_column_inner(
+        db,
+        &payload.task_id,
+        &payload.target_column,
+        payload.force.unwrap_or(false),
+    )?;
+    let mut column = db
+        .tasks
+        .iter()
+        .filter(|t| t.board == moved.board && t.column == moved.column && t.id != moved.id)
+        .collect::<Vec<_>>();
+    column.sort_by(|a, b| compare_task_order(a, b));
+    let mut ids = column.into_iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+    ids.insert(payload.target_index.min(ids.len()), moved.id.clone());
+
+    let now = now_iso();
+    for (index, id) in ids.iter().enumerate() {
+        if let Some(task) = db.tasks.iter_mut().find(|t| &t.id == id) {
+            let order = Some(index as f64);
+            if task.order != order {
+                task.order = order;
+                task.updated = Some(now.clone());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+#[tauri::command]
+fn move_task_between_columns_with_position(
+    app: AppHandle,
+    payload: MoveTaskWithPositionPayload,
+) -> std::result::Result<Vec<String>, ErrorResponse> {
+    (|| -> Result<Vec<String>> {
+        let ids = with_locked_db(&db_path(&app)?, |db| {
+            move_task_with_position_inner(db, &payload)
+        })?;
+        log_activity(
+            &app,
+            "move",
+            "task",
+            &payload.task_id,
+            json!({ "column": payload.target_column, "index": payload.target_index }),
+        );
+        Ok(ids)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestTagsPayload {
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Tags the card already has; they are never suggested again.
+    #[serde(default)]
+    pub existing_tags: Vec<String>,
+}
+
+const MAX_SUGGESTED_TAGS: usize = 6;
+const SUGGEST_TAGS_SYSTEM_PROMPT: &str = "You label kanban cards. Only return JSON, no markdown.";
+
+fn suggest_tags_prompt(payload: &SuggestTagsPayload) -> String {
+    format!(
+        "Suggest 3 to 6 concise lowercase tags (one or two words each) for this card. Return JSON only: {{\"tags\": [\"...\"]}}.\n\nTitle: {}\nBody: {}\nAlready tagged: {}",
+        payload.title,
+        payload.body.clone().unwrap_or_default(),
+        payload.existing_tags.join(", ")
+    )
+}
+
+// Accepts `{"tags": [...]}` or a comma-separated string, then normalizes to `kebab-case`,
+// drops duplicates and anything the card already has, and caps the list.
+fn parse_suggested_tags(content: &str, existing: &[String]) -> Result<Vec<String>> {
+    let malformed = |reason: String| VaultError::OpenAiResponse {
+        reason,
+        snippet: response_snippet(content),
+    };
+    let object = extract_json_object(strip_code_fences(content))
+        .ok_or_else(|| malformed("no JSON object found".to_string()))?;
+    let value: serde_json::Value =
+        serde_json::from_str(object).map_err(|e| malformed(e.to_string()))?;
+    let raw = match value.get("tags") {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect::<Vec<_>>(),
+        Some(serde_json::Value::String(list)) => list.split(',').map(str::to_string).collect(),
+        _ => return Err(malformed("missing keys: tags".to_string())),
+    };
+
+    let existing = existing
+        .iter()
+        .map(|t| t.trim().to_lowercase())
+        .collect::<BTreeSet<_>>();
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw {
+        let tag = tag
+            .trim()
+            .trim_start_matches('#')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if tag.is_empty() || existing.contains(&tag) || tags.contains(&tag) {
+            continue;
+        }
+        tags.push(tag);
+        if tags.len() == MAX_SUGGESTED_TAGS {
+            break;
+        }
+    }
+    Ok(tags)
+}
+
+#[tauri::command]
+async fn openai_suggest_tags(
+    app: AppHandle,
+    usage: tauri::State<'_, OpenAiUsageState>,
+    payload: SuggestTagsPayload,
+) -> std::result::Result<Vec<String>, ErrorResponse> {
+    (async move {
+        if openai_mock_enabled() {
+            let content = mock_suggest_tags_content(&payload);
+            return parse_suggested_tags(&content, &payload.existing_tags);
+        }
+        let prompt = suggest_tags_prompt(&payload);
+        let (response, model) =
+            request_openai_json(&app, SUGGEST_TAGS_SYSTEM_PROMPT, &prompt, false).await?;
+        let value: serde_json::Value = response.json().await.map_err(openai_request_error)?;
+        if let Ok(mut totals) = usage.0.lock() {
+            record_openai_usage(&mut totals, &value, &model);
+        }
+        parse_suggested_tags(completion_content(&value), &payload.existing_tags)
+    })
+    .await
+    .map_err(ErrorResponse::from)
+}
+
+const OPENAI_MOCK_ENV: &str = "OPENAI_MOCK";
+
+/// `OPENAI_MOCK=1` answers every OpenAI command locally, without a key or network access.
+fn openai_mock_enabled() -> bool {
+    std::env::var(OPENAI_MOCK_ENV)
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+// Shaped like a model reply, so the mock goes through the same parsing as real answers.
+fn mock_autofill_content(payload: &OpenAiAutoFillPayload) -> String {
+    let given = |field: &Option<String>| field.clone().filter(|v| !v.trim().is_empty());
+    let description = payload.description.trim();
+    let first_line = description.lines().next().unwrap_or("").trim();
+    let title = given(&payload.title).unwrap_or_else(|| {
+        let words = first_line.split_whitespace().take(6).collect::<Vec<_>>();
+        if words.is_empty() {
+            "Untitled story".to_string()
+        } else {
+            words.join(" ")
+        }
+    });
+    let i_want = given(&payload.i_want).unwrap_or_else(|| {
+        if first_line.is_empty() {
+            title.to_lowercase()
+        } else {
+            first_line.to_string()
+        }
+    });
+    let criteria = payload
+        .acceptance_criteria
+        .clone()
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| vec![format!("Given \"{title}\" is done, then {i_want}")]);
+    json!({
+        "title": title,
+        "asA": given(&payload.as_a).unwrap_or_else(|| "user".to_string()),
+        "iWant": i_want,
+        "soThat": given(&payload.so_that).unwrap_or_else(|| "my work gets done".to_string()),
+        "acceptanceCriteria": criteria,
+    })
+    .to_string()
+}
+
+fn mock_suggest_tags_content(payload: &SuggestTagsPayload) -> String {
+    let tags = payload
+        .title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 3)
+        .collect::<Vec<_>>();
+    json!({ "tags": tags }).to_string()
+}
+
+const COLUMN_TEMPLATES_DIR: &str = "templates/columns";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnTemplate {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ColumnTemplateFile {
+    columns: Vec<String>,
+}
+
+fn column_template_path(vault: &Path, name: &str) -> Result<PathBuf> {
+    let name = sanitize_id(name.trim())?;
+    Ok(vault
+        .join(COLUMN_TEMPLATES_DIR)
+        .join(format!("{name}.json")))
+}
+
+// Files that don't parse are skipped rather than hiding every other template.
+fn list_column_templates_inner(vault: &Path) -> Result<Vec<ColumnTemplate>> {
+    let dir = vault.join(COLUMN_TEMPLATES_DIR);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut templates = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let parsed = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<ColumnTemplateFile>(&raw).ok());
+        if let Some(file) = parsed {
+            templates.push(ColumnTemplate {
+                name: name.to_string(),
+                columns: file.columns,
+            });
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+fn load_column_template(vault: &Path, name: &str) -> Result<ColumnTemplate> {
+    let path = column_template_path(vault, name)?;
+    if !path.is_file() {
+        return Err(VaultError::InvalidData(format!(
+            "column template not found: {name}"
+        )));
+    }
+    let file: ColumnTemplateFile = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    Ok(ColumnTemplate {
+        name: name.trim().to_string(),
+        columns: normalize_columns(file.columns)?,
+    })
+}
+
+fn save_column_template_inner(
+    vault: &Path,
+    name: &str,
+    columns: Vec<String>,
+) -> Result<ColumnTemplate> {
+    let path = column_template_path(vault, name)?;
+    let columns = normalize_columns(columns)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let raw = serde_json::to_string_pretty(&ColumnTemplateFile {
+        columns: columns.clone(),
+    })?;
+    write_atomic(&path, raw.as_bytes())?;
+    Ok(ColumnTemplate {
+        name: name.trim().to_string(),
+        columns,
+    })
+}
+
+fn create_board_from_template_inner(
+    db: &mut Db,
+    title: &str,
+    template: &ColumnTemplate,
+    slug_ids: bool,
+) -> Result<Board> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(VaultError::InvalidData("title cannot be empty".to_string()));
+    }
+    let board = Board {
+        id: new_entity_id(db, "board", title, slug_ids),
+        title: title.to_string(),
+        columns: normalize_columns(template.columns.clone())?,
+        wip_limits: BTreeMap::new(),
+        done_columns: default_done_columns(),
+    };
+    db.boards.push(board.clone());
+    Ok(board)
+}
+
+#[tauri::command]
+fn list_column_templates(
+    app: AppHandle,
+) -> std::result::Result<Vec<ColumnTemplate>, ErrorResponse> {
+    (|| -> Result<Vec<ColumnTemplate>> { list_column_templates_inner(&vault_dir(&app)?) })()
+        .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn save_column_template(
+    app: AppHandle,
+    name: String,
+    columns: Vec<String>,
+) -> std::result::Result<ColumnTemplate, ErrorResponse> {
+    (|| -> Result<ColumnTemplate> {
+        let template = save_column_template_inner(&vault_dir(&app)?, &name, columns)?;
+        log_activity(
+            &app,
+            "save",
+            "column_template",
+            &template.name,
+            json!({ "columns": template.columns }),
+        );
+        Ok(template)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[tauri::command]
+fn create_board_from_template(
+    app: AppHandle,
+    title: String,
+    template: String,
+) -> std::result::Result<Board, ErrorResponse> {
+    (|| -> Result<Board> {
+        let template = load_column_template(&vault_dir(&app)?, &template)?;
+        let slug_ids = slug_ids_enabled(&app)?;
+        let board = with_locked_db(&db_path(&app)?, |db| {
+            create_board_from_template_inner(db, &title, &template, slug_ids)
+        })?;
+        log_activity(
+            &app,
+            "create",
+            "board",
+            &board.id,
+            json!({ "title": board.title, "template": template.name }),
+        );
+        Ok(board)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DueDay {
+    pub date: String,
+    pub tasks: Vec<Task>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DueCalendar {
+    pub days: Vec<DueDay>,
+    pub unparseable: usize,
+}
+
+fn parse_calendar_date(raw: &str, field: &str) -> Result<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .map_err(|_| VaultError::InvalidData(format!("{field} must be a YYYY-MM-DD date")))
+}
+
+fn get_tasks_due_between_inner(
+    db: &Db,
+    board_id: Option<&str>,
+    from: &str,
+    to: &str,
+) -> Result<DueCalendar> {
+    let from = parse_calendar_date(from, "from")?;
+    let to = parse_calendar_date(to, "to")?;
+    if from > to {
+        return Err(VaultError::InvalidData(
+            "from must not be after to".to_string(),
+        ));
+    }
+
+    let mut by_date: BTreeMap<chrono::NaiveDate, Vec<Task>> = BTreeMap::new();
+    let mut unparseable = 0;
+    for task in list_tasks_inner(db, board_id) {
+        let Some(due) = task.due.as_deref().filter(|due| !due.trim().is_empty()) else {
+            continue;
+        };
+        match parse_due(due) {
+            Some(date) if (from..=to).contains(&date) => {
+                by_date.entry(date).or_default().push(task)
+            }
+            Some(_) => {}
+            None => unparseable += 1,
+        }
+    }
+
+    let days = by_date
+        .into_iter()
+        .map(|(date, mut tasks)| {
+            tasks.sort_by(|a, b| a.title.cmp(&b.title));
+            DueDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                tasks,
+            }
+        })
+        .collect();
+    Ok(DueCalendar { days, unparseable })
+}
+
+#[tauri::command]
+fn get_tasks_due_between(
+    app: AppHandle,
+    from: String,
+    to: String,
+    board_id: Option<String>,
+) -> std::result::Result<DueCalendar, ErrorResponse> {
+    (|| -> Result<DueCalendar> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        get_tasks_due_between_inner(&db, board_id.as_deref(), &from, &to)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+fn render_board_csv(board: &BoardWithTasks) -> Result<String> {
+    let csv_error = |e: csv::Error| VaultError::InvalidData(format!("could not write CSV: {e}"));
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer
+        .write_record(["id", "title", "column", "tags", "due", "created", "updated"])
+        .map_err(csv_error)?;
+    for column in &board.columns {
+        for task in &column.tasks {
+            writer
+                .write_record([
+                    task.id.as_str(),
+                    task.title.as_str(),
+                    column.name.as_str(),
+                    task.tags.join(";").as_str(),
+                    task.due.as_deref().unwrap_or_default(),
+                    task.created.as_deref().unwrap_or_default(),
+                    task.updated.as_deref().unwrap_or_default(),
+                ])
+                .map_err(csv_error)?;
+        }
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| VaultError::InvalidData(format!("could not write CSV: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| VaultError::InvalidData(e.to_string()))
+}
+
+#[tauri::command]
+fn export_board_csv(
+    app: AppHandle,
+    board_id: String,
+) -> std::result::Result<String, ErrorResponse> {
+    (|| -> Result<String> {
+        let path = db_path(&app)?;
+        let db = load_db(&path)?;
+        let board = board_with_tasks_inner(&db, &board_id)?;
+        render_board_csv(&board)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+const TASK_COLORS: [&str; 8] = [
+    "red", "orange", "yellow", "green", "teal", "blue", "purple", "gray",
+];
+
+fn normalize_color(color: Option<&str>) -> Result<Option<String>> {
+    let Some(raw) = color.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+    let lowered = raw.to_lowercase();
+    let is_hex = lowered.len() == 7
+        && lowered.starts_with('#')
+        && lowered[1..].bytes().all(|b| b.is_ascii_hexdigit());
+    if is_hex || TASK_COLORS.contains(&lowered.as_str()) {
+        Ok(Some(lowered))
+    } else {
+        Err(VaultError::InvalidData(format!(
+            "color must be #rrggbb or one of {}: {raw}",
+            TASK_COLORS.join(", ")
+        )))
+    }
+}
+
+fn set_task_color_inner(db: &mut Db, task_id: &str, color: Option<&str>) -> Result<Task> {
+    let color = normalize_color(color)?;
+    edit_task_inner(db, task_id, |task| {
+        task.color = color;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn set_task_color(
+    app: AppHandle,
+    task_id: String,
+    color: Option<String>,
+) -> std::result::Result<Task, ErrorResponse> {
+    (|| -> Result<Task> {
+        let task = with_locked_db(&db_path(&app)?, |db| {
+            set_task_color_inner(db, &task_id, color.as_deref())
+        })?;
+        log_activity(
+            &app,
+            "set_color",
+            "task",
+            &task.id,
+            json!({ "color": task.color }),
+        );
+        Ok(task)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkTagResult {
+    pub task_id: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+fn bulk_tag_inner(
+    db: &mut Db,
+    task_ids: &[String],
+    tag: &str,
+    case_insensitive: bool,
+) -> Result<Vec<BulkTagResult>> {
+    // A bad tag would fail every id the same way, so reject it up front.
+    normalize_tag(tag)?;
+    Ok(task_ids
+        .iter()
+        .map(|task_id| {
+            let outcome = add_task_tag_inner(db, task_id, tag, case_insensitive);
+            BulkTagResult {
+                task_id: task_id.clone(),
+                ok: outcome.is_ok(),
+                error: outcome.err().map(ErrorResponse::from),
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn bulk_tag(
+    app: AppHandle,
+    task_ids: Vec<String>,
+    tag: String,
+    case_insensitive: Option<bool>,
+) -> std::result::Result<Vec<BulkTagResult>, ErrorResponse> {
+    (|| -> Result<Vec<BulkTagResult>> {
+        let results = with_locked_db_if(&db_path(&app)?, |db| {
+            let results = bulk_tag_inner(db, &task_ids, &tag, case_insensitive.unwrap_or(false))?;
+            let changed = results.iter().any(|r| r.ok);
+            Ok((results, changed))
+        })?;
+        let tagged = results
+            .iter()
+            .filter(|r| r.ok)
+            .map(|r| r.task_id.as_str())
+            .collect::<Vec<_>>();
+        if !tagged.is_empty() {
+            log_activity(
+                &app,
+                "bulk_tag",
+                "tag",
+                tag.trim(),
+                json!({ "taskIds": tagged }),
+            );
+        }
+        Ok(results)
+    })()
+    .map_err(ErrorResponse::from)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSizeEntry {
+    /// A top-level file (`pm-db.json` holds every board, task, project, epic and the archive) or directory.
+    pub name: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSizeReport {
+    pub total_files: u64,
+    pub total_bytes: u64,
+    /// Largest first.
+    pub entries: Vec<VaultSizeEntry>,
+}
+
+// Symlinks are skipped so a link out of the vault can't inflate the report or loop forever.
+fn add_path_size(path: &Path, entry: &mut VaultSizeEntry) -> Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for child in fs::read_dir(path)? {
+            add_path_size(&child?.path(), entry)?;
+        }
+    } else if meta.is_file() {
+        entry.files += 1;
+        entry.bytes += meta.len();
+    }
+    Ok(())
+}
+
+fn vault_size_report_inner(vault: &Path) -> Result<VaultSizeReport> {
+    let mut report = VaultSizeReport::default();
+    if vault.is_dir() {
+        for child in fs::read_dir(vault)? {
+            let child = child?;
+            let mut entry = VaultSizeEntry {
+                name: child.file_name().to_string_lossy().to_string(),
+                ..VaultSizeEntry::default()
+            };
+            add_path_size(&child.path(), &mut entry)?;
+            report.entries.push(entry);
+        }
+    }
+    // Always listed, so the UI has a row for attachments before the first upload.
+    if !report
+        .entries
+        .iter()
+        .any(|e| e.name == ATTACHMENTS_DIR_NAME)
+    {
+        report.entries.push(VaultSizeEntry {
+            name: ATTACHMENTS_DIR_NAME.to_string(),
+            ..VaultSizeEntry::default()
+        });
+    }
+    report.total_files = report.entries.iter().map(|e| e.files).sum();
+    report.total_bytes = report.entries.iter().map(|e| e.bytes).sum();
+    report
+        .entries
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(report)
+}
+
+#[tauri::command]
+fn vault_size_report(app: AppHandle) -> std::result::Result<VaultSizeReport, ErrorResponse> {
+    (|| -> Result<VaultSizeReport> { vault_size_report_inner(&vault_dir(&app)?) })()
+        .map_err(ErrorResponse::from)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .manage(OpenAiUsageState::default())
+        .manage(FrontmatterCheckpointState::default())
+        .manage(ActiveVaultState::default())
+        .manage(VaultWatcherState::default())
+        .setup(|app| {
+            // Live reload is a convenience; an unwatchable vault or exhausted inotify limit
+            // shouldn't keep the app from opening.
+            if let Err(e) = start_vault_watcher(app.handle().clone()) {
+                eprintln!("vault watcher disabled: {e}");
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            vault_info,
+            list_boards,
+            list_tasks,
+            get_board_with_tasks,
+            update_task_column,
+            list_projects,
             list_epics,
             create_project,
             create_epic,
             create_story,
-            openai_autofill_story
+            openai_autofill_story,
+            find_description_body_drift,
+            rename_column,
+            export_board_html,
+            reorder_tasks,
+            migrate_timestamps_to_iso,
+            tasks_by_due_bucket,
+            move_task_to_board,
+            merge_boards,
+            search_tasks,
+            board_previews,
+            flatten_board,
+            openai_usage,
+            reset_openai_usage,
+            vault_diagnostics,
+            topological_task_order,
+            save_task_frontmatter_checkpoint,
+            restore_task_frontmatter,
+            set_openai_key,
+            task_column_journey,
+            get_task,
+            promote_overdue,
+            task_canonical,
+            export_board_markdown,
+            export_vault_json,
+            import_vault_json,
+            archive_task,
+            unarchive_task,
+            list_archived_tasks,
+            toggle_checklist_item,
+            add_checklist_item,
+            remove_checklist_item,
+            get_epic_progress,
+            find_duplicate_ids,
+            delete_project,
+            update_project,
+            update_epic,
+            duplicate_task,
+            openai_autofill_story_stream,
+            list_all_tasks_across_boards,
+            set_task_due,
+            add_task_tag,
+            remove_task_tag,
+            rename_tag,
+            list_tags,
+            bulk_move_column,
+            get_vault_config,
+            set_vault_config,
+            set_active_vault,
+            list_known_vaults,
+            reveal_vault,
+            add_dependency,
+            remove_dependency,
+            get_blocked_tasks,
+            get_activity_log,
+            get_task_cycle_time,
+            board_throughput,
+            update_board,
+            clear_vault_cache,
+            validate_vault,
+            repair_task_columns,
+            get_board_swimlanes,
+            set_task_priority,
+            create_stories,
+            import_stories_csv,
+            export_due_ics,
+            git_commit_vault,
+            set_task_recurrence,
+            materialize_recurring_tasks,
+            link_task_to_epic,
+            unlink_task_from_epic,
+            get_project_tree,
+            count_tasks_by_column,
+            vault_stats,
+            attach_file,
+            list_attachments,
+            remove_attachment,
+            undo_last_change,
+            get_board,
+            delete_epic,
+            move_task_between_columns_with_position,
+            openai_suggest_tags,
+            list_column_templates,
+            save_column_template,
+            create_board_from_template,
+            get_tasks_due_between,
+            export_board_csv,
+            set_task_color,
+            bulk_tag,
+            vault_size_report
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("{}.json", generate_id("pm-db-test")));
+        path
+    }
+
+    #[test]
+    fn creates_default_db() {
+        let path = temp_path();
+        ensure_db(&path).unwrap();
+        let db = load_db(&path).unwrap();
+        assert_eq!(db.boards.len(), 1);
+        assert!(db.tasks.is_empty());
+        let _ = fs::remove_file(path);
+    }
+
+    fn sample_task(id: &str, title: &str, column: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            board: "default".to_string(),
+            column: column.to_string(),
+            tags: vec![],
+            due: None,
+            created: None,
+            updated: None,
+            project_id: None,
+            epic_id: None,
+            owner: None,
+            description: None,
+            as_a: None,
+            i_want: None,
+            so_that: None,
+            acceptance_criteria: None,
+            order: None,
+            transitions: vec![],
+            blocked_by: vec![],
+            checklist: vec![],
+            archived: None,
+            priority: None,
+            recurrence: None,
+            recurrence_of: None,
+            recurrence_date: None,
+            attachments: vec![],
+            color: None,
+            due_status: None,
+            custom: BTreeMap::new(),
+            body: String::new(),
+        }
+    }
+
+    fn sample_project(id: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            title: id.to_string(),
+            owner: None,
+            created: None,
+            updated: None,
+            description: None,
+        }
+    }
+
+    fn sample_epic(id: &str, project_id: Option<&str>) -> Epic {
+        Epic {
+            id: id.to_string(),
+            title: id.to_string(),
+            project_id: project_id.map(|p| p.to_string()),
+            owner: None,
+            created: None,
+            updated: None,
+            description: None,
+        }
+    }
+
+    fn sample_board(id: &str, columns: &[&str]) -> Board {
+        Board {
+            id: id.to_string(),
+            title: id.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            wip_limits: BTreeMap::new(),
+            done_columns: default_done_columns(),
+        }
+    }
+
+    #[test]
+    fn flags_body_edited_without_description() {
+        let mut db = default_db();
+        let mut edited = sample_task("story-1", "Edited", "Backlog");
+        edited.description = Some("Original text".to_string());
+        edited.body = "Original text, plus notes".to_string();
+        let mut in_sync = sample_task("story-2", "In sync", "Backlog");
+        in_sync.description = Some("Same".to_string());
+        in_sync.body = "Same\n".to_string();
+        db.tasks = vec![edited, in_sync];
+
+        let drifted = description_body_drift_inner(&mut db, None);
+        assert_eq!(drifted.len(), 1);
+        assert_eq!(drifted[0].task_id, "story-1");
+        assert_eq!(db.tasks[0].description.as_deref(), Some("Original text"));
+
+        description_body_drift_inner(&mut db, Some(DriftSource::Body));
+        assert_eq!(
+            db.tasks[0].description.as_deref(),
+            Some("Original text, plus notes")
+        );
+        assert!(description_body_drift_inner(&mut db, None).is_empty());
+    }
+
+    #[test]
+    fn rename_column_migrates_tasks() {
+        let mut db = default_db();
+        db.boards[0].wip_limits.insert("In Progress".to_string(), 3);
+        db.tasks = vec![
+            sample_task("story-1", "First", "In Progress"),
+            sample_task("story-2", "Second", "Backlog"),
+        ];
+
+        let migrated = rename_column_inner(&mut db, "default", " In Progress ", "Doing").unwrap();
+        assert_eq!(migrated, vec!["story-1".to_string()]);
+        assert!(db.boards[0].columns.contains(&"Doing".to_string()));
+        assert!(!db.boards[0].columns.contains(&"In Progress".to_string()));
+        assert_eq!(db.boards[0].wip_limits.get("Doing"), Some(&3));
+        assert!(!db.boards[0].wip_limits.contains_key("In Progress"));
+
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let doing = board.columns.iter().find(|c| c.name == "Doing").unwrap();
+        assert_eq!(doing.tasks.len(), 1);
+        assert!(db.tasks[0].updated.is_some());
+        assert!(db.tasks[1].updated.is_none());
+
+        assert!(rename_column_inner(&mut db, "default", "Missing", "Other").is_err());
+        assert!(rename_column_inner(&mut db, "default", "Doing", "Done").is_err());
+
+        rename_column_inner(&mut db, "default", "Done", "Shipped").unwrap();
+        assert_eq!(db.boards[0].done_columns, vec!["Shipped"]);
+    }
+
+    #[test]
+    fn markdown_html_drops_unsafe_link_targets() {
+        let html = render_markdown_html(
+            "[xss](javascript:alert(1)) [caps](JavaScript:alert(2)) ![img](data:image/png;base64,AAAA)",
+        );
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(!html.contains("data:"));
+        assert!(html.contains(">xss</a>"));
+
+        let html = render_markdown_html(
+            "[site](https://example.com) [mail](mailto:a@b.c) [doc](notes/plan.md) [top](#top)",
+        );
+        for href in [
+            "https://example.com",
+            "mailto:a@b.c",
+            "notes/plan.md",
+            "#top",
+        ] {
+            assert!(html.contains(&format!("href=\"{href}\"")), "{href}");
+        }
+    }
+
+    #[test]
+    fn board_html_lists_columns_and_escapes_titles() {
+        let mut db = default_db();
+        let mut card = sample_task("story-1", "Fix <script>alert(1)</script>", "Backlog");
+        card.tags = vec!["backend".to_string()];
+        card.due = Some("2026-03-01".to_string());
+        card.body = "Some **bold** notes <b>raw</b>".to_string();
+        db.tasks = vec![card, sample_task("story-2", "Ship it", "Done")];
+
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let html = render_board_html(&board);
+        for column in &db.boards[0].columns {
+            assert!(html.contains(&format!("<h2>{column} ")));
+        }
+        assert!(html.contains("Fix &lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("Ship it"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(!html.contains("<b>raw</b>"));
+        assert!(html.contains("Due 2026-03-01"));
+    }
+
+    #[test]
+    fn persisted_order_wins_over_title_sort() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("story-a", "Alpha", "Backlog"),
+            sample_task("story-b", "Bravo", "Backlog"),
+            sample_task("story-c", "Charlie", "Backlog"),
+        ];
+
+        reorder_tasks_inner(
+            &mut db,
+            "default",
+            "Backlog",
+            &["story-c".to_string(), "story-a".to_string()],
+        )
+        .unwrap();
+
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let backlog = board.columns.iter().find(|c| c.name == "Backlog").unwrap();
+        let ids = backlog
+            .tasks
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["story-c", "story-a", "story-b"]);
+
+        assert!(reorder_tasks_inner(&mut db, "default", "Done", &["story-a".to_string()]).is_err());
+        let twice = ["story-a".to_string(), "story-a".to_string()];
+        assert!(reorder_tasks_inner(&mut db, "default", "Backlog", &twice).is_err());
+
+        db.boards.push(sample_board("ops", &["Backlog"]));
+        let mut other = sample_task("story-d", "Delta", "Backlog");
+        other.board = "ops".to_string();
+        db.tasks.push(other);
+        let mixed = ["story-a".to_string(), "story-d".to_string()];
+        assert!(reorder_tasks_inner(&mut db, "default", "Backlog", &mixed).is_err());
+        assert_eq!(db.tasks[0].order, Some(1.0));
+        assert_eq!(db.tasks[3].order, None);
+    }
+
+    #[test]
+    fn migrates_epoch_timestamps_only() {
+        let mut db = default_db();
+        let mut legacy = sample_task("story-1", "Legacy", "Backlog");
+        legacy.created = Some("1738889234".to_string());
+        let mut current = sample_task("story-2", "Current", "Backlog");
+        current.created = Some("2025-02-07T00:47:14Z".to_string());
+        current.updated = Some("2026-02-06".to_string());
+        current.transitions = vec![Transition {
+            from: "Backlog".to_string(),
+            to: "Ready".to_string(),
+            at: "2026-02-06T10:00:00Z".to_string(),
+        }];
+        let mut moved = sample_task("story-3", "Moved", "Ready");
+        moved.transitions = vec![Transition {
+            from: "Backlog".to_string(),
+            to: "Ready".to_string(),
+            at: "1738889234".to_string(),
+        }];
+        let mut archived = sample_task("story-4", "Archived", "Done");
+        archived.updated = Some("1738889234".to_string());
+        db.tasks = vec![legacy, current, moved];
+        db.archive = vec![archived];
+        let mut project = sample_project("p1");
+        project.created = Some("1738889234".to_string());
+        db.projects = vec![project];
+
+        assert_eq!(migrate_timestamps_to_iso_inner(&mut db), 4);
+        assert_eq!(db.tasks[2].transitions[0].at, "2025-02-07T00:47:14Z");
+        assert_eq!(db.tasks[1].transitions[0].at, "2026-02-06T10:00:00Z");
+        assert_eq!(
+            db.archive[0].updated.as_deref(),
+            Some("2025-02-07T00:47:14Z")
+        );
+        assert_eq!(
+            db.projects[0].created.as_deref(),
+            Some("2025-02-07T00:47:14Z")
+        );
+        assert_eq!(db.tasks[0].created.as_deref(), Some("2025-02-07T00:47:14Z"));
+        assert_eq!(db.tasks[0].updated, None);
+        assert_eq!(db.tasks[1].created.as_deref(), Some("2025-02-07T00:47:14Z"));
+        assert_eq!(db.tasks[1].updated.as_deref(), Some("2026-02-06"));
+
+        assert_eq!(migrate_timestamps_to_iso_inner(&mut db), 0);
+    }
+
+    #[test]
+    fn reads_accept_epoch_and_iso_timestamps() {
+        assert!(chrono::DateTime::parse_from_rfc3339(&now_iso()).is_ok());
+
+        let mut db = default_db();
+        let mut legacy = sample_task("story-1", "Legacy", "Backlog");
+        legacy.created = Some("1738889234".to_string());
+        legacy.updated = Some("2026-02-06".to_string());
+        db.tasks = vec![legacy];
+
+        let tasks = list_tasks_inner(&db, None);
+        assert_eq!(tasks[0].created.as_deref(), Some("2025-02-07T00:47:14Z"));
+        assert_eq!(tasks[0].updated.as_deref(), Some("2026-02-06"));
+        assert_eq!(db.tasks[0].created.as_deref(), Some("1738889234"));
+    }
+
+    #[test]
+    fn buckets_tasks_by_due_date() {
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let mut db = default_db();
+        let mut yesterday = sample_task("story-1", "Yesterday", "Backlog");
+        yesterday.due = Some("2026-03-09".to_string());
+        let mut due_today = sample_task("story-2", "Today", "Backlog");
+        due_today.due = Some("2026-03-10T18:00:00Z".to_string());
+        let mut this_week = sample_task("story-3", "Soon", "Backlog");
+        this_week.due = Some("2026-03-16".to_string());
+        let mut later = sample_task("story-4", "Later", "Backlog");
+        later.due = Some("2026-03-17".to_string());
+        let undated = sample_task("story-5", "Undated", "Backlog");
+        let mut done = sample_task("story-6", "Finished", "Done");
+        done.due = Some("2026-03-01".to_string());
+        db.tasks = vec![yesterday, due_today, this_week, later, undated, done];
+
+        let result = tasks_by_due_bucket_inner(&db, None, false, today);
+        let ids = |kind: DueBucketKind| {
+            result
+                .buckets
+                .iter()
+                .find(|b| b.bucket == kind)
+                .unwrap()
+                .tasks
+                .iter()
+                .map(|t| t.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(DueBucketKind::Overdue), vec!["story-1"]);
+        assert_eq!(ids(DueBucketKind::Today), vec!["story-2"]);
+        assert_eq!(ids(DueBucketKind::ThisWeek), vec!["story-3"]);
+        assert_eq!(ids(DueBucketKind::Later), vec!["story-4"]);
+        assert_eq!(ids(DueBucketKind::NoDate), vec!["story-5"]);
+        assert_eq!(result.excluded.len(), 1);
+
+        let with_done = tasks_by_due_bucket_inner(&db, None, true, today);
+        assert!(with_done.excluded.is_empty());
+        assert_eq!(with_done.buckets[0].tasks.len(), 2);
+    }
+
+    #[test]
+    fn moves_task_between_boards() {
+        let mut db = default_db();
+        db.boards.push(sample_board("ops", &["Todo", "Doing"]));
+        db.tasks = vec![sample_task("story-1", "Deploy", "Backlog")];
+
+        let moved = move_task_to_board_inner(&mut db, "story-1", "ops", None, false).unwrap();
+        assert_eq!(moved.board, "ops");
+        assert_eq!(moved.column, "Todo");
+        assert!(moved.updated.is_some());
+
+        let moved =
+            move_task_to_board_inner(&mut db, "story-1", "ops", Some("Doing"), false).unwrap();
+        assert_eq!(moved.column, "Doing");
+
+        assert!(matches!(
+            move_task_to_board_inner(&mut db, "story-1", "ops", Some("Backlog"), false),
+            Err(VaultError::ColumnNotFound(_, _))
+        ));
+        assert!(matches!(
+            move_task_to_board_inner(&mut db, "story-1", "missing", None, false),
+            Err(VaultError::BoardNotFound(_))
+        ));
+        assert_eq!(db.tasks[0].column, "Doing");
+    }
+
+    #[test]
+    fn merges_source_board_into_target() {
+        let mut db = default_db();
+        db.boards
+            .push(sample_board("legacy", &["Todo", "Doing", "Shipped"]));
+        let mut todo = sample_task("story-1", "Todo card", "Todo");
+        todo.board = "legacy".to_string();
+        let mut doing = sample_task("story-2", "Doing card", "Doing");
+        doing.board = "legacy".to_string();
+        let mut shipped = sample_task("story-3", "Shipped card", "Shipped");
+        shipped.board = "legacy".to_string();
+        db.tasks = vec![
+            todo,
+            doing,
+            shipped,
+            sample_task("story-4", "Native", "Ready"),
+        ];
+
+        let mut column_map = HashMap::new();
+        column_map.insert("Doing".to_string(), "In Progress".to_string());
+        column_map.insert("Shipped".to_string(), "Done".to_string());
+
+        let mut bad_map = column_map.clone();
+        bad_map.insert("Todo".to_string(), "Nowhere".to_string());
+        assert!(merge_boards_inner(&mut db, "legacy", "default", &bad_map).is_err());
+        assert_eq!(db.boards.len(), 2);
+
+        assert_eq!(
+            merge_boards_inner(&mut db, "legacy", "default", &column_map).unwrap(),
+            3
+        );
+        assert_eq!(db.boards.len(), 1);
+        assert!(db.tasks.iter().all(|t| t.board == "default"));
+        let column_of = |id: &str| db.tasks.iter().find(|t| t.id == id).unwrap().column.clone();
+        assert_eq!(column_of("story-1"), "Inbox");
+        assert_eq!(column_of("story-2"), "In Progress");
+        assert_eq!(column_of("story-3"), "Done");
+        assert_eq!(column_of("story-4"), "Ready");
+    }
+
+    #[test]
+    fn update_task_column_rejects_unknown_columns() {
+        let mut db = default_db();
+        let mut orphan = sample_task("story-2", "Orphan", "Backlog");
+        orphan.board = "deleted".to_string();
+        db.tasks = vec![sample_task("story-1", "Card", "Backlog"), orphan];
+
+        let moved = update_task_column_inner(&mut db, "story-1", "Review", false).unwrap();
+        assert_eq!(moved.column, "Review");
+
+        let err = update_task_column_inner(&mut db, "story-1", "Reveiw", false).unwrap_err();
+        assert!(matches!(err, VaultError::ColumnNotFound(_, _)));
+        assert_eq!(db.tasks[0].column, "Review");
+
+        let err = update_task_column_inner(&mut db, "story-2", "Backlog", false).unwrap_err();
+        assert!(err.to_string().starts_with("board not found"));
+    }
+
+    fn story_payload(title: &str) -> CreateStoryPayload {
+        CreateStoryPayload {
+            title: title.to_string(),
+            project_id: None,
+            epic_id: None,
+            owner: None,
+            description: None,
+            as_a: None,
+            i_want: None,
+            so_that: None,
+            acceptance_criteria: None,
+            column: None,
+            board_id: None,
+        }
+    }
+
+    #[test]
+    fn create_story_requires_an_existing_board() {
+        let mut db = default_db();
+        let created = create_story_inner(&mut db, story_payload("On default"), false).unwrap();
+        assert_eq!(created.board, "default");
+
+        let mut payload = story_payload("Nowhere");
+        payload.board_id = Some("missing".to_string());
+        let err = create_story_inner(&mut db, payload, false).unwrap_err();
+        assert!(matches!(err, VaultError::BoardNotFound(_)));
+        assert_eq!(db.tasks.len(), 1);
+
+        db.boards.push(sample_board("ops", &["Triage", "Done"]));
+        let mut payload = story_payload("Wrong column");
+        payload.board_id = Some("ops".to_string());
+        payload.column = Some("Review".to_string());
+        let err = create_story_inner(&mut db, payload, false).unwrap_err();
+        assert!(matches!(err, VaultError::ColumnNotFound(_, _)));
+        let mut payload = story_payload("Default column");
+        payload.board_id = Some("ops".to_string());
+        assert_eq!(
+            create_story_inner(&mut db, payload, false).unwrap().column,
+            "Triage"
+        );
+        let mut payload = story_payload("Right column");
+        payload.board_id = Some("ops".to_string());
+        payload.column = Some("Triage".to_string());
+        assert_eq!(
+            create_story_inner(&mut db, payload, false).unwrap().column,
+            "Triage"
+        );
+        assert_eq!(db.tasks.len(), 3);
+
+        // A vault configured without a Backlog column still takes stories from the wizard.
+        let config = VaultConfig {
+            default_columns: Some(vec!["Todo".to_string(), "Done".to_string()]),
+            ..VaultConfig::default()
+        };
+        let mut db = default_db_for(&config);
+        let created = create_story_inner(&mut db, story_payload("Wizard"), false).unwrap();
+        assert_eq!(created.column, "Todo");
+    }
+
+    #[test]
+    fn search_ranks_title_matches_first() {
+        let mut db = default_db();
+        let mut body_only = sample_task("story-1", "Alpha", "Backlog");
+        body_only.body = "Fix the login redirect".to_string();
+        let title_hit = sample_task("story-2", "Login redirect loop", "Backlog");
+        let mut mixed = sample_task("story-3", "Login page", "Backlog");
+        mixed.body = "Redirect after submit".to_string();
+        let unrelated = sample_task("story-4", "Logout", "Backlog");
+        db.tasks = vec![body_only, title_hit, mixed, unrelated];
+
+        let ids = search_tasks_inner(&db, "LOGIN redirect", None, 50)
+            .into_iter()
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["story-2", "story-3", "story-1"]);
+
+        assert_eq!(search_tasks_inner(&db, "login", None, 1).len(), 1);
+        assert!(search_tasks_inner(&db, "   ", None, 50).is_empty());
+    }
+
+    #[test]
+    fn wip_limits_block_moves_unless_forced() {
+        let mut db = default_db();
+        db.boards[0].wip_limits.insert("In Progress".to_string(), 1);
+        db.tasks = vec![
+            sample_task("story-1", "Busy", "In Progress"),
+            sample_task("story-2", "Waiting", "Ready"),
+        ];
+
+        let err = update_task_column_inner(&mut db, "story-2", "In Progress", false).unwrap_err();
+        assert!(matches!(err, VaultError::WipLimitExceeded(_, 1)));
+        assert_eq!(db.tasks[1].column, "Ready");
+
+        // Re-saving a card into the column it already occupies doesn't count against it.
+        update_task_column_inner(&mut db, "story-1", "In Progress", false).unwrap();
+        // Unlimited columns stay unlimited.
+        update_task_column_inner(&mut db, "story-1", "Review", false).unwrap();
+        update_task_column_inner(&mut db, "story-2", "Review", false).unwrap();
+
+        update_task_column_inner(&mut db, "story-1", "In Progress", false).unwrap();
+        update_task_column_inner(&mut db, "story-2", "In Progress", true).unwrap();
+        assert_eq!(db.tasks[1].column, "In Progress");
+
+        let mut ops = sample_board("ops", &["Todo", "Doing"]);
+        ops.wip_limits.insert("Todo".to_string(), 0);
+        db.boards.push(ops);
+        assert!(matches!(
+            move_task_to_board_inner(&mut db, "story-1", "ops", None, false),
+            Err(VaultError::WipLimitExceeded(_, 0))
+        ));
+    }
+
+    #[test]
+    fn previews_count_cards_and_pick_first_title() {
+        let mut db = default_db();
+        db.boards.push(sample_board("ops", &["Todo"]));
+        let mut ops_card = sample_task("story-4", "Rotate keys", "Todo");
+        ops_card.board = "ops".to_string();
+        db.tasks = vec![
+            sample_task("story-1", "Zebra", "Backlog"),
+            sample_task("story-2", "Apple", "Backlog"),
+            sample_task("story-3", "Mango", "Done"),
+            ops_card,
+        ];
+
+        let previews = board_previews_inner(&db);
+        assert_eq!(previews.len(), 2);
+        let default = previews.iter().find(|p| p.id == "default").unwrap();
+        let backlog = default
+            .columns
+            .iter()
+            .find(|c| c.name == "Backlog")
+            .unwrap();
+        assert_eq!(backlog.count, 2);
+        assert_eq!(backlog.first_title.as_deref(), Some("Apple"));
+        let inbox = default.columns.iter().find(|c| c.name == "Inbox").unwrap();
+        assert_eq!(inbox.count, 0);
+        assert_eq!(inbox.first_title, None);
+
+        let ops = previews.iter().find(|p| p.id == "ops").unwrap();
+        assert_eq!(ops.columns[0].count, 1);
+        assert_eq!(ops.columns[0].first_title.as_deref(), Some("Rotate keys"));
+    }
+
+    #[test]
+    fn flatten_board_records_original_columns() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("story-1", "One", "Backlog"),
+            sample_task("story-2", "Two", "Review"),
+            sample_task("story-3", "Three", "Inbox"),
+        ];
+
+        assert!(flatten_board_inner(&mut db, "default", "Nowhere").is_err());
+        assert_eq!(flatten_board_inner(&mut db, "default", "Inbox").unwrap(), 2);
+        assert!(db.tasks.iter().all(|t| t.column == "Inbox"));
+        assert_eq!(db.tasks[0].transitions[0].from, "Backlog");
+        assert_eq!(db.tasks[0].transitions[0].to, "Inbox");
+        assert_eq!(db.tasks[1].transitions[0].from, "Review");
+        assert!(db.tasks[2].transitions.is_empty());
+    }
+
+    #[test]
+    fn parses_autofill_content_defensively() {
+        let fenced = "```json\n{\"title\":\"Login\",\"asA\":\"user\",\"iWant\":\"to sign in\",\"soThat\":\"I can work\",\"acceptanceCriteria\":[\"Given {x}\"]}\n```";
+        let parsed = parse_autofill_content(fenced).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("Login"));
+        assert_eq!(
+            parsed.acceptance_criteria,
+            Some(vec!["Given {x}".to_string()])
+        );
+
+        let prose = "Sure! Here is the story:\n{\"title\":null,\"asA\":\"admin\",\"iWant\":null,\"soThat\":null,\"acceptanceCriteria\":[]} Hope that helps.";
+        let parsed = parse_autofill_content(prose).unwrap();
+        assert_eq!(parsed.as_a.as_deref(), Some("admin"));
+
+        let single = "{\"title\":null,\"asA\":null,\"iWant\":null,\"soThat\":null,\"acceptanceCriteria\":\"Works offline\"}";
+        let parsed = parse_autofill_content(single).unwrap();
+        assert_eq!(
+            parsed.acceptance_criteria,
+            Some(vec!["Works offline".to_string()])
+        );
+        let mixed = "{\"title\":null,\"asA\":null,\"iWant\":null,\"soThat\":null,\"acceptanceCriteria\":[1,null,{\"given\":\"x\"}]}";
+        let parsed = parse_autofill_content(mixed).unwrap();
+        assert_eq!(
+            parsed.acceptance_criteria,
+            Some(vec!["1".to_string(), "{\"given\":\"x\"}".to_string()])
+        );
+
+        let err = parse_autofill_content("{\"title\":\"Only a title\"}").unwrap_err();
+        assert!(err.to_string().contains("missing keys: asA"));
+        let err = parse_autofill_content("I cannot help with that.").unwrap_err();
+        assert!(err.to_string().contains("I cannot help with that."));
+    }
+
+    #[test]
+    fn atomic_writes_never_expose_partial_content() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("pm-db-atomic-{}.json", std::process::id()));
+        let old = "a".repeat(256 * 1024);
+        let new = "b".repeat(512 * 1024);
+        write_atomic(&path, old.as_bytes()).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            let (old, new) = (old.clone(), new.clone());
+            std::thread::spawn(move || {
+                for i in 0..20 {
+                    let contents = if i % 2 == 0 { &new } else { &old };
+                    write_atomic(&path, contents.as_bytes()).unwrap();
+                }
+            })
+        };
+        while !writer.is_finished() {
+            let contents = fs::read_to_string(&path).unwrap();
+            assert!(
+                contents == old || contents == new,
+                "saw {} bytes",
+                contents.len()
+            );
+        }
+        writer.join().unwrap();
+
+        let leftovers = fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.starts_with(&format!(".{}", path.file_name().unwrap().to_string_lossy()))
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn records_openai_usage_per_model() {
+        let mut totals = OpenAiUsage::default();
+        let response = json!({
+            "model": "gpt-4o-mini-2024-07-18",
+            "usage": { "prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150 }
+        });
+        record_openai_usage(&mut totals, &response, "gpt-4o-mini");
+        record_openai_usage(&mut totals, &response, "gpt-4o-mini");
+        record_openai_usage(&mut totals, &json!({ "choices": [] }), "gpt-4o-mini");
+
+        assert_eq!(totals.total.requests, 2);
+        assert_eq!(totals.total.prompt_tokens, 240);
+        assert_eq!(totals.total.total_tokens, 300);
+        assert_eq!(
+            totals.by_model["gpt-4o-mini-2024-07-18"].completion_tokens,
+            60
+        );
+        assert_eq!(totals.by_model.len(), 1);
+    }
+
+    #[test]
+    fn vault_diagnostics_reports_unparseable_entries() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("t1", "Good", "Todo"));
+        let mut value = serde_json::to_value(&db).unwrap();
+        value["tasks"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "id": "t2", "title": 42 }));
+        value["epics"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "name": "no id" }));
+
+        let issues = vault_diagnostics_inner(&value.to_string());
+        let paths = issues.iter().map(|i| i.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["tasks[1] (t2)", "epics[0]"]);
+        assert!(issues.iter().all(|i| !i.error.is_empty()));
+
+        assert!(vault_diagnostics_inner(&serde_json::to_string(&db).unwrap()).is_empty());
+        assert_eq!(vault_diagnostics_inner("{ not json")[0].path, "$");
+    }
+
+    #[test]
+    fn topological_order_puts_blockers_first() {
+        let mut db = default_db();
+        let mut deploy = sample_task("deploy", "A deploy", "Todo");
+        deploy.blocked_by = vec!["test".to_string()];
+        let mut test = sample_task("test", "B test", "Todo");
+        test.blocked_by = vec!["build".to_string(), "elsewhere".to_string()];
+        let build = sample_task("build", "C build", "Todo");
+        let docs = sample_task("docs", "D docs", "Todo");
+        db.tasks = vec![deploy, test, build, docs];
+
+        let order = topological_task_order_inner(&db, "default").unwrap();
+        let ids = order.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["build", "test", "deploy", "docs"]);
+
+        db.tasks[2].blocked_by = vec!["deploy".to_string()];
+        match topological_task_order_inner(&db, "default") {
+            Err(VaultError::DependencyCycle(ids)) => assert_eq!(ids, "build, deploy, test"),
+            other => panic!("expected a cycle, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn openai_retries_only_rate_limits_and_server_errors() {
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(reqwest::StatusCode::UNAUTHORIZED));
+
+        assert!(matches!(
+            openai_status_error(reqwest::StatusCode::TOO_MANY_REQUESTS, "slow down"),
+            VaultError::OpenAiRateLimited
+        ));
+        assert!(matches!(
+            openai_status_error(reqwest::StatusCode::BAD_REQUEST, "bad"),
+            VaultError::InvalidData(_)
+        ));
+        let quota = r#"{"error":{"message":"You exceeded your current quota, please check your plan and billing details.","type":"insufficient_quota","code":"insufficient_quota"}}"#;
+        assert!(is_quota_error(quota));
+        assert!(!is_quota_error("Rate limit reached for gpt-4o-mini"));
+        let billing = r#"{"error":{"message":"Invalid billing address on file.","type":"invalid_request_error"}}"#;
+        assert!(matches!(
+            openai_status_error(reqwest::StatusCode::BAD_REQUEST, billing),
+            VaultError::InvalidData(_)
+        ));
+        let err = openai_status_error(reqwest::StatusCode::TOO_MANY_REQUESTS, quota);
+        assert_eq!(err.code(), "openai_quota");
+    }
+
+    #[test]
+    fn restoring_frontmatter_checkpoint_keeps_current_body() {
+        let mut db = default_db();
+        let mut task = sample_task("t1", "Original", "Backlog");
+        task.tags = vec!["api".to_string()];
+        task.body = "first draft".to_string();
+        db.tasks.push(task);
+
+        let mut checkpoints = FrontmatterCheckpoints::default();
+        let token = save_task_frontmatter_checkpoint_inner(&db, &mut checkpoints, "t1").unwrap();
+
+        let task = &mut db.tasks[0];
+        task.title = "Renamed".to_string();
+        task.tags = vec!["ui".to_string(), "urgent".to_string()];
+        task.owner = Some("sam".to_string());
+        task.column = "Ready".to_string();
+        task.body = "second draft".to_string();
+        task.attachments = vec!["spec.pdf".to_string()];
+
+        let restored = restore_task_frontmatter_inner(&mut db, &checkpoints, &token).unwrap();
+        assert_eq!(restored.title, "Original");
+        assert_eq!(restored.tags, vec!["api".to_string()]);
+        assert_eq!(restored.owner, None);
+        assert_eq!(restored.column, "Backlog");
+        assert_eq!(restored.body, "second draft");
+        assert_eq!(db.tasks[0].body, "second draft");
+        assert_eq!(restored.attachments, vec!["spec.pdf".to_string()]);
+
+        assert!(restore_task_frontmatter_inner(&mut db, &checkpoints, "t1#99").is_err());
+
+        db.boards[0].columns.retain(|c| c != "Backlog");
+        let restored = restore_task_frontmatter_inner(&mut db, &checkpoints, &token).unwrap();
+        assert_eq!(restored.column, "Inbox");
+    }
+
+    #[test]
+    fn openai_key_is_read_from_config_file() {
+        let path = std::env::temp_dir().join(format!("{}.json", generate_id("pm-config-test")));
+        assert!(matches!(
+            resolve_openai_key(&load_config(&path).unwrap()),
+            Err(VaultError::OpenAiKeyMissing)
+        ));
+
+        let config = VaultConfig {
+            openai_api_key: Some("sk-test ".to_string()),
+            ..VaultConfig::default()
+        };
+        save_config(&path, &config).unwrap();
+        let loaded = load_config(&path).unwrap();
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            assert_eq!(resolve_openai_key(&loaded).unwrap(), "sk-test");
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn column_journey_follows_transitions() {
+        let mut task = sample_task("t1", "Card", "Inbox");
+        task.created = Some("2024-03-01T09:00:00Z".to_string());
+        assert_eq!(
+            task_column_journey_inner(&task),
+            vec![JourneyStep {
+                column: "Inbox".to_string(),
+                entered_at: Some("2024-03-01T09:00:00Z".to_string()),
+            }]
+        );
+
+        set_task_column(&mut task, "Ready", "2024-03-02T09:00:00Z");
+        set_task_column(&mut task, "Done", "2024-03-03T09:00:00Z");
+        let journey = task_column_journey_inner(&task);
+        let steps = journey
+            .iter()
+            .map(|s| (s.column.as_str(), s.entered_at.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            vec![
+                ("Inbox", Some("2024-03-01T09:00:00Z")),
+                ("Ready", Some("2024-03-02T09:00:00Z")),
+                ("Done", Some("2024-03-03T09:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn openai_endpoint_supports_azure() {
+        let default = openai_endpoint_from(None, None, None);
+        assert_eq!(default.url, "https://api.openai.com/v1/chat/completions");
+        assert!(!default.azure);
+
+        let azure = openai_endpoint_from(
+            Some("https://acme.openai.azure.com/openai/deployments/gpt4o/chat/completions".into()),
+            Some("azure".into()),
+            Some("2024-02-01".into()),
+        );
+        assert!(azure.azure);
+        assert_eq!(
+            azure.url,
+            "https://acme.openai.azure.com/openai/deployments/gpt4o/chat/completions?api-version=2024-02-01"
+        );
+    }
+
+    #[test]
+    fn get_task_returns_single_task() {
+        let mut db = default_db();
+        let mut task = sample_task("t1", "Card", "Todo");
+        task.created = Some("1700000000".to_string());
+        db.tasks.push(task);
+
+        let found = get_task_inner(&db, "t1").unwrap();
+        assert_eq!(found.title, "Card");
+        assert_eq!(found.created.as_deref(), Some("2023-11-14T22:13:20Z"));
+        assert!(matches!(
+            get_task_inner(&db, "missing"),
+            Err(VaultError::TaskNotFound(id)) if id == "missing"
+        ));
+    }
+
+    #[test]
+    fn promote_overdue_moves_only_overdue_tasks() {
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let mut db = default_db();
+        let mut late = sample_task("late", "Late", "Backlog");
+        late.due = Some("2024-05-01".to_string());
+        let mut on_time = sample_task("on-time", "On time", "Backlog");
+        on_time.due = Some("2024-05-20".to_string());
+        let mut elsewhere = sample_task("elsewhere", "Elsewhere", "Inbox");
+        elsewhere.due = Some("2024-05-01".to_string());
+        db.tasks = vec![late, on_time, elsewhere];
+
+        let payload = PromoteOverduePayload {
+            board_id: "default".to_string(),
+            from_column: "Backlog".to_string(),
+            to_column: "Ready".to_string(),
+        };
+        let moved = promote_overdue_inner(&mut db, &payload, today).unwrap();
+        assert_eq!(moved, vec!["late".to_string()]);
+        assert_eq!(db.tasks[0].column, "Ready");
+        assert_eq!(db.tasks[0].transitions.len(), 1);
+        assert_eq!(db.tasks[1].column, "Backlog");
+        assert_eq!(db.tasks[2].column, "Inbox");
+
+        let bad = PromoteOverduePayload {
+            to_column: "Nope".to_string(),
+            ..payload
+        };
+        assert!(matches!(
+            promote_overdue_inner(&mut db, &bad, today),
+            Err(VaultError::ColumnNotFound(_, column)) if column == "Nope"
+        ));
+    }
+
+    #[test]
+    fn canonical_task_ignores_key_order_and_formatting() {
+        let a: Task = serde_json::from_str(
+            r#"{"id":"t1","title":"Card","board":"default","column":"Todo","tags":["x"],"created":"1700000000","body":"Notes\n"}"#,
+        )
+        .unwrap();
+        let b: Task = serde_json::from_str(
+            r#"{"body":"  Notes","created":"2023-11-14T22:13:20Z","tags":["x"],"column":"Todo","board":"default","title":"Card","id":"t1"}"#,
+        )
+        .unwrap();
+
+        let canonical = task_canonical_inner(&a).unwrap();
+        assert_eq!(canonical, task_canonical_inner(&b).unwrap());
+        assert!(canonical.starts_with(r#"{"blocked_by":[],"board":"default","body":"Notes""#));
+
+        let mut c = b.clone();
+        c.title = "Other".to_string();
+        assert_ne!(canonical, task_canonical_inner(&c).unwrap());
+    }
+
+    #[test]
+    fn story_fields_round_trip_as_camel_case() {
+        let legacy: Task = serde_json::from_str(
+            r#"{"id":"s1","title":"Story","board":"default","column":"Inbox","as_a":"user","i_want":"export","so_that":"I can share","acceptance_criteria":["works"],"project_id":"p1","epic_id":"e1"}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.as_a.as_deref(), Some("user"));
+        assert_eq!(legacy.epic_id.as_deref(), Some("e1"));
+
+        let value = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(value["asA"], "user");
+        assert_eq!(value["iWant"], "export");
+        assert_eq!(value["soThat"], "I can share");
+        assert_eq!(value["acceptanceCriteria"], json!(["works"]));
+        assert_eq!(value["projectId"], "p1");
+        assert!(value.get("as_a").is_none());
+
+        let round_trip: Task = serde_json::from_value(value).unwrap();
+        assert_eq!(round_trip.so_that.as_deref(), Some("I can share"));
+
+        let plain = serde_json::to_value(sample_task("t1", "Plain", "Todo")).unwrap();
+        assert!(plain.get("asA").is_none());
+        assert!(plain.get("projectId").is_none());
+    }
+
+    #[test]
+    fn exports_board_as_markdown() {
+        let mut db = default_db();
+        db.boards = vec![sample_board("default", &["Todo", "Doing", "Done"])];
+        let mut first = sample_task("t1", "Write spec", "Todo");
+        first.tags = vec!["docs".to_string()];
+        first.due = Some("2024-05-01".to_string());
+        first.order = Some(1.0);
+        let mut second = sample_task("t2", "Another", "Todo");
+        second.order = Some(2.0);
+        db.tasks = vec![second, first, sample_task("t3", "Ship", "Done")];
+
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let markdown = render_board_markdown(&board);
+        assert_eq!(
+            markdown,
+            "# default\n\n## Todo\n\n- [ ] Write spec (due 2024-05-01) `#docs`\n- [ ] Another\n\n## Doing\n\n_No tasks_\n\n## Done\n\n- [x] Ship\n"
+        );
+
+        db.boards[0].title = "# Team\nboard".to_string();
+        db.boards[0].columns[1] = "- Doing".to_string();
+        db.tasks = vec![sample_task("t4", "Fix *login*\n# again", "- Doing")];
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let markdown = render_board_markdown(&board);
+        assert!(markdown.starts_with("# \\# Team board\n"));
+        assert!(markdown.contains("\n## \\- Doing\n\n- [ ] Fix \\*login\\* # again\n"));
+        assert_eq!(markdown.lines().filter(|l| l.starts_with('#')).count(), 4);
+    }
+
+    #[test]
+    fn vault_export_includes_every_collection() {
+        let mut db = default_db();
+        let mut story = sample_task("s1", "Story", "Inbox");
+        story.body = "Full body".to_string();
+        story.as_a = Some("user".to_string());
+        db.tasks.push(story);
+        db.projects.push(Project {
+            id: "project-1".to_string(),
+            title: "Project".to_string(),
+            owner: None,
+            created: Some("1700000000".to_string()),
+            updated: None,
+            description: None,
+        });
+
+        db.archive.push(sample_task("old", "Archived", "Done"));
+
+        let export = export_vault_inner(&db);
+        assert_eq!(export.schema_version, VAULT_EXPORT_SCHEMA_VERSION);
+        assert_eq!(export.archive[0].id, "old");
+        assert_eq!(export.boards.len(), 1);
+        assert_eq!(export.projects[0].id, "project-1");
+        assert_eq!(
+            export.projects[0].created.as_deref(),
+            Some("2023-11-14T22:13:20Z")
+        );
+        assert_eq!(export.tasks[0].body, "Full body");
+
+        let value = serde_json::to_value(&export).unwrap();
+        assert_eq!(value["schemaVersion"], 2);
+        assert_eq!(value["tasks"][0]["asA"], "user");
+    }
+
+    #[test]
+    fn import_vault_handles_id_collisions() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("t1", "Existing", "Inbox"));
+        let mut incoming = sample_task("t1", "Incoming", "Backlog");
+        incoming.body = "imported body".to_string();
+        let mut dependent = sample_task("t2", "Dependent", "Inbox");
+        dependent.blocked_by = vec!["t1".to_string()];
+        let bundle = VaultExport {
+            schema_version: VAULT_EXPORT_SCHEMA_VERSION,
+            exported_at: now_iso(),
+            boards: vec![],
+            tasks: vec![incoming, dependent],
+            projects: vec![],
+            epics: vec![],
+            archive: vec![],
+        };
+
+        let mut skipped = default_db();
+        skipped.tasks = db.tasks.clone();
+        let summary = import_vault_inner(&mut skipped, bundle.clone(), ImportMode::Skip).unwrap();
+        assert_eq!((summary.created, summary.skipped), (1, 1));
+        assert_eq!(skipped.tasks[0].title, "Existing");
+
+        let mut overwritten = default_db();
+        overwritten.tasks = db.tasks.clone();
+        let summary =
+            import_vault_inner(&mut overwritten, bundle.clone(), ImportMode::Overwrite).unwrap();
+        assert_eq!(summary.overwritten, 1);
+        assert_eq!(overwritten.tasks[0].body, "imported body");
+
+        let summary = import_vault_inner(&mut db, bundle.clone(), ImportMode::Rename).unwrap();
+        assert_eq!((summary.created, summary.renamed), (1, 1));
+        let ids = db.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["t1", "t1-imported", "t2"]);
+        assert_eq!(db.tasks[2].blocked_by, vec!["t1-imported".to_string()]);
+
+        let mut bad = bundle;
+        bad.tasks[1].column = "Nowhere".to_string();
+        let before = db.tasks.len();
+        assert!(import_vault_inner(&mut db, bad, ImportMode::Rename).is_err());
+        assert_eq!(db.tasks.len(), before);
+    }
+
+    #[test]
+    fn vault_export_round_trips_archived_tasks() {
+        let mut source = default_db();
+        source.tasks.push(sample_task("live", "Live", "Inbox"));
+        let mut old = sample_task("old", "Old", "Removed column");
+        old.archived = Some("2024-01-01T10:00:00Z".to_string());
+        source.archive.push(old);
+        let raw = serde_json::to_string(&export_vault_inner(&source)).unwrap();
+        let bundle: VaultExport = serde_json::from_str(&raw).unwrap();
+
+        let mut fresh = default_db();
+        import_vault_inner(&mut fresh, bundle.clone(), ImportMode::Skip).unwrap();
+        assert_eq!(fresh.tasks.len(), 1);
+        assert_eq!(fresh.archive[0].id, "old");
+
+        // An archived id already in use counts as a collision.
+        let mut bundle = bundle;
+        bundle.boards.clear();
+        let mut db = default_db();
+        db.archive
+            .push(sample_task("live", "Archived here", "Done"));
+        let summary = import_vault_inner(&mut db, bundle.clone(), ImportMode::Rename).unwrap();
+        assert_eq!(summary.renamed, 1);
+        assert_eq!(db.tasks[0].id, "live-imported");
+        assert_eq!(
+            db.archive.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(),
+            vec!["live", "old"]
+        );
+
+        let mut db = default_db();
+        db.archive
+            .push(sample_task("live", "Archived here", "Done"));
+        import_vault_inner(&mut db, bundle, ImportMode::Overwrite).unwrap();
+        assert_eq!(db.tasks[0].title, "Live");
+        assert_eq!(db.archive.len(), 1);
+    }
+
+    #[test]
+    fn archiving_hides_task_until_unarchived() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("t1", "Finished", "Done"));
+        db.tasks.push(sample_task("t2", "Open", "Inbox"));
+
+        let archived = archive_task_inner(&mut db, "t1").unwrap();
+        assert!(archived.archived.is_some());
+        let live = list_tasks_inner(&db, Some("default"));
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].id, "t2");
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        assert_eq!(
+            board.columns.iter().map(|c| c.tasks.len()).sum::<usize>(),
+            1
+        );
+
+        let raw = serde_json::to_string(&db).unwrap();
+        let reloaded: Db = serde_json::from_str(&raw).unwrap();
+        assert_eq!(reloaded.archive[0].id, "t1");
+
+        let restored = unarchive_task_inner(&mut db, "t1").unwrap();
+        assert_eq!(restored.archived, None);
+        assert_eq!(restored.column, "Done");
+        assert!(db.archive.is_empty());
+        assert_eq!(list_tasks_inner(&db, None).len(), 2);
+        assert!(matches!(
+            unarchive_task_inner(&mut db, "t1"),
+            Err(VaultError::TaskNotFound(_))
+        ));
+
+        archive_task_inner(&mut db, "t2").unwrap();
+        db.boards[0].columns.retain(|c| c != "Inbox");
+        assert_eq!(
+            unarchive_task_inner(&mut db, "t2").unwrap().column,
+            "Backlog"
+        );
+    }
+
+    #[test]
+    fn lists_archived_tasks_newest_first() {
+        let mut db = default_db();
+        assert!(list_archived_tasks_inner(&db).is_empty());
+
+        let mut older = sample_task("old", "Old", "Done");
+        older.archived = Some("2024-01-01T10:00:00Z".to_string());
+        let mut newer = sample_task("new", "New", "Review");
+        newer.archived = Some("2024-02-01T10:00:00Z".to_string());
+        db.archive = vec![older, newer];
+
+        let archived = list_archived_tasks_inner(&db);
+        let ids = archived.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["new", "old"]);
+        assert_eq!(archived[0].column, "Review");
+        assert_eq!(archived[0].board, "default");
+    }
+
+    #[test]
+    fn checklist_items_can_be_added_toggled_and_removed() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("t1", "Story", "Inbox"));
+
+        add_checklist_item_inner(&mut db, "t1", "Write tests").unwrap();
+        add_checklist_item_inner(&mut db, "t1", "Update docs").unwrap();
+        let task = toggle_checklist_item_inner(&mut db, "t1", 0).unwrap();
+        assert!(task.checklist[0].done);
+        assert!(!task.checklist[1].done);
+        assert!(task.updated.is_some());
+
+        let task = remove_checklist_item_inner(&mut db, "t1", 1).unwrap();
+        assert_eq!(
+            task.checklist,
+            vec![ChecklistItem {
+                text: "Write tests".to_string(),
+                done: true,
+            }]
+        );
+        assert!(toggle_checklist_item_inner(&mut db, "t1", 5).is_err());
+
+        let raw = serde_json::to_string(&db.tasks[0]).unwrap();
+        let parsed: Task = serde_json::from_str(&raw).unwrap();
+        assert_eq!(parsed.checklist, db.tasks[0].checklist);
+        let plain: Task = serde_json::from_str(
+            r#"{"id":"t2","title":"Plain","board":"default","column":"Inbox"}"#,
+        )
+        .unwrap();
+        assert!(plain.checklist.is_empty());
+    }
+
+    #[test]
+    fn epic_progress_counts_done_stories() {
+        let mut db = default_db();
+        db.epics.push(sample_epic("epic-1", None));
+        assert_eq!(
+            epic_progress_inner(&db, "epic-1", None).unwrap(),
+            EpicProgress {
+                total: 0,
+                done: 0,
+                percent: 0,
+            }
+        );
+
+        for (id, column) in [("s1", "Done"), ("s2", "Review"), ("s3", "Inbox")] {
+            let mut story = sample_task(id, id, column);
+            story.epic_id = Some("epic-1".to_string());
+            db.tasks.push(story);
+        }
+        db.tasks.push(sample_task("other", "Unlinked", "Done"));
+
+        let progress = epic_progress_inner(&db, "epic-1", None).unwrap();
+        assert_eq!(
+            (progress.total, progress.done, progress.percent),
+            (3, 1, 33)
+        );
+        let progress = epic_progress_inner(&db, "epic-1", Some("Review")).unwrap();
+        assert_eq!(progress.done, 1);
+        assert!(matches!(
+            epic_progress_inner(&db, "missing", None),
+            Err(VaultError::EpicNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn parses_db_with_editor_artifacts() {
+        let raw = serde_json::to_string_pretty(&default_db()).unwrap();
+
+        let crlf = raw.replace('\n', "\r\n");
+        assert_eq!(parse_db(&crlf).unwrap().boards.len(), 1);
+        let no_trailing_newline = raw.trim_end();
+        assert!(parse_db(no_trailing_newline).is_ok());
+        let with_bom = format!("\u{feff}{raw}  \n");
+        assert!(parse_db(&with_bom).is_ok());
+        assert!(vault_diagnostics_inner(&with_bom).is_empty());
+
+        let truncated = &raw[..raw.len() - 2];
+        assert!(parse_db(truncated).is_err());
+    }
+
+    #[test]
+    fn finds_duplicate_ids_and_new_ids_do_not_collide() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("story-1", "First", "Inbox"));
+        db.tasks.push(sample_task("story-2", "Second", "Inbox"));
+        db.tasks.push(sample_task("story-1", "Copy", "Inbox"));
+        db.archive.push(sample_task("story-2", "Archived", "Done"));
+
+        assert_eq!(
+            find_duplicate_ids_inner(&db),
+            vec![
+                DuplicateId {
+                    id: "story-1".to_string(),
+                    locations: vec!["tasks[0]".to_string(), "tasks[2]".to_string()],
+                },
+                DuplicateId {
+                    id: "story-2".to_string(),
+                    locations: vec!["tasks[1]".to_string(), "archive[0]".to_string()],
+                },
+            ]
+        );
+
+        let first = create_story_inner(&mut db, story_payload("A"), false).unwrap();
+        let second = create_story_inner(&mut db, story_payload("B"), false).unwrap();
+        assert_ne!(first.id, second.id);
+        assert!(first.id.starts_with("story-") && second.id.starts_with("story-"));
+    }
+
+    #[test]
+    fn generated_ids_are_unique_in_a_tight_loop() {
+        let mut db = default_db();
+        let mut ids = std::collections::HashSet::new();
+        for i in 0..1000 {
+            let story =
+                create_story_inner(&mut db, story_payload(&format!("Story {i}")), false).unwrap();
+            assert!(story.id.starts_with("story-"));
+            ids.insert(story.id);
+        }
+        assert_eq!(ids.len(), 1000);
+        assert!(generate_id("project").starts_with("project-"));
+        assert!(generate_id("epic").starts_with("epic-"));
+    }
+
+    #[test]
+    fn delete_project_requires_cascade_for_linked_epics() {
+        let mut db = default_db();
+        db.projects.push(sample_project("project-1"));
+        db.epics.push(sample_epic("epic-1", Some("project-1")));
+        let mut story = sample_task("s1", "Story", "Inbox");
+        story.project_id = Some("project-1".to_string());
+        story.epic_id = Some("epic-1".to_string());
+        db.tasks.push(story);
+        db.tasks.push(sample_task("s2", "Unrelated", "Inbox"));
+
+        let err = delete_project_inner(&mut db, "project-1", false).unwrap_err();
+        assert!(err.to_string().contains("1 linked epic"));
+        assert_eq!(db.projects.len(), 1);
+
+        let summary = delete_project_inner(&mut db, "project-1", true).unwrap();
+        assert_eq!(summary.epics_removed, vec!["epic-1".to_string()]);
+        assert_eq!(summary.tasks_updated, vec!["s1".to_string()]);
+        assert!(db.projects.is_empty() && db.epics.is_empty());
+        assert_eq!(db.tasks[0].project_id, None);
+        assert_eq!(db.tasks[0].epic_id, None);
+        assert!(matches!(
+            delete_project_inner(&mut db, "project-1", true),
+            Err(VaultError::ProjectNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn updates_projects_and_epics() {
+        let mut db = default_db();
+        db.projects.push(sample_project("project-1"));
+        db.projects.push(sample_project("project-2"));
+        db.epics.push(sample_epic("epic-1", Some("project-1")));
+
+        let project = update_project_inner(
+            &mut db,
+            UpdateProjectPayload {
+                id: "project-1".to_string(),
+                title: Some("Renamed".to_string()),
+                owner: Some("sam".to_string()),
+                description: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(project.title, "Renamed");
+        assert_eq!(project.owner.as_deref(), Some("sam"));
+        assert!(project.updated.is_some());
+
+        let epic = update_epic_inner(
+            &mut db,
+            UpdateEpicPayload {
+                id: "epic-1".to_string(),
+                title: None,
+                project_id: Some("project-2".to_string()),
+                owner: None,
+                description: Some(String::new()),
+            },
+        )
+        .unwrap();
+        assert_eq!(epic.title, "epic-1");
+        assert_eq!(epic.project_id.as_deref(), Some("project-2"));
+        assert_eq!(epic.description, None);
+
+        let missing = UpdateEpicPayload {
+            id: "epic-1".to_string(),
+            title: None,
+            project_id: Some("project-9".to_string()),
+            owner: None,
+            description: None,
+        };
+        assert!(matches!(
+            update_epic_inner(&mut db, missing),
+            Err(VaultError::ProjectNotFound(_))
+        ));
+        assert!(matches!(
+            update_project_inner(
+                &mut db,
+                UpdateProjectPayload {
+                    id: "nope".to_string(),
+                    title: None,
+                    owner: None,
+                    description: None,
+                },
+            ),
+            Err(VaultError::ProjectNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn duplicates_task_with_fresh_id() {
+        let mut db = default_db();
+        let mut payload = story_payload("Login");
+        payload.acceptance_criteria = Some(vec!["Accepts SSO".to_string()]);
+        let source = create_story_inner(&mut db, payload, false).unwrap();
+        update_task_column_inner(&mut db, &source.id, "Ready", false).unwrap();
+        let before = serde_json::to_string(&db.tasks[0]).unwrap();
+
+        let copy = duplicate_task_inner(&mut db, &source.id, false).unwrap();
+        assert_ne!(copy.id, source.id);
+        assert!(copy.id.starts_with("story-"));
+        assert_eq!(copy.title, "Login (copy)");
+        assert_eq!(copy.column, "Ready");
+        assert_eq!(
+            copy.acceptance_criteria,
+            Some(vec!["Accepts SSO".to_string()])
+        );
+        assert_eq!(copy.body, source.body);
+        assert!(copy.transitions.is_empty());
+        assert_eq!(copy.updated, None);
+        assert_eq!(db.tasks.len(), 2);
+        assert_eq!(serde_json::to_string(&db.tasks[0]).unwrap(), before);
+
+        let slugged = create_story_inner(&mut db, story_payload("Fix login bug"), true).unwrap();
+        let copy = duplicate_task_inner(&mut db, &slugged.id, true).unwrap();
+        assert_eq!(copy.id, "fix-login-bug-copy");
+        let again = duplicate_task_inner(&mut db, &slugged.id, true).unwrap();
+        assert_eq!(again.id, "fix-login-bug-copy-2");
+    }
+
+    #[test]
+    fn autofill_stream_accumulates_split_chunks() {
+        let chunk = |content: &str| {
+            let delta = json!({ "choices": [{ "delta": { "content": content } }] });
+            format!("data: {delta}\n\n")
+        };
+        let first = chunk(r#"{"title":"Login","asA":null,"#);
+        let second = chunk(r#""iWant":null,"soThat":null,"acceptanceCriteria":[]}"#);
+        let usage = json!({
+            "model": "gpt-4o-mini",
+            "choices": [],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 3, "total_tokens": 8 }
+        });
+
+        let mut stream = AutofillStream::default();
+        // Split the first event mid-line to mimic chunk boundaries.
+        let (head, tail) = first.split_at(20);
+        let mut deltas = stream.push(head.as_bytes());
+        assert!(deltas.is_empty());
+        deltas.extend(stream.push(tail.as_bytes()));
+        deltas.extend(stream.push(second.as_bytes()));
+        deltas.extend(stream.push(format!("data: {usage}\n\ndata: [DONE]").as_bytes()));
+        deltas.extend(stream.finish());
+        assert_eq!(deltas.len(), 2);
+        assert!(stream.done);
+
+        let mut totals = OpenAiUsage::default();
+        record_openai_usage(&mut totals, stream.usage_chunk.as_ref().unwrap(), "x");
+        assert_eq!(totals.total.total_tokens, 8);
+        assert_eq!(
+            stream.into_response().unwrap().title.as_deref(),
+            Some("Login")
+        );
+
+        let mut broken = AutofillStream::default();
+        broken.push(format!("{}data: {{not json\n", chunk("{")).as_bytes());
+        let err = broken.into_response().unwrap_err().to_string();
+        assert!(err.contains("malformed stream chunk"), "{err}");
+
+        let mut failed = AutofillStream::default();
+        failed.push(b"data: {\"error\":{\"message\":\"server overloaded\"}}\n");
+        assert!(failed
+            .into_response()
+            .unwrap_err()
+            .to_string()
+            .contains("server overloaded"));
+    }
+
+    #[test]
+    fn openai_temperature_falls_back_outside_valid_range() {
+        assert_eq!(parse_openai_temperature(None), DEFAULT_OPENAI_TEMPERATURE);
+        assert_eq!(parse_openai_temperature(Some("0.2")), 0.2);
+        assert_eq!(parse_openai_temperature(Some(" 2 ")), 2.0);
+        assert_eq!(
+            parse_openai_temperature(Some("2.5")),
+            DEFAULT_OPENAI_TEMPERATURE
+        );
+        assert_eq!(
+            parse_openai_temperature(Some("-1")),
+            DEFAULT_OPENAI_TEMPERATURE
+        );
+        assert_eq!(
+            parse_openai_temperature(Some("warm")),
+            DEFAULT_OPENAI_TEMPERATURE
+        );
+        assert_eq!(
+            parse_openai_temperature(Some("NaN")),
+            DEFAULT_OPENAI_TEMPERATURE
+        );
+    }
+
+    #[test]
+    fn lists_tasks_across_boards_with_titles() {
+        let mut db = default_db();
+        let mut later = sample_task("later", "B later", "Inbox");
+        later.due = Some("2024-06-01".to_string());
+        let mut sooner = sample_task("sooner", "Z sooner", "Inbox");
+        sooner.due = Some("2024-05-01".to_string());
+        let mut orphan = sample_task("orphan", "A orphan", "Inbox");
+        orphan.board = "gone".to_string();
+        db.tasks = vec![orphan, later, sooner];
+
+        let tasks = list_all_tasks_across_boards_inner(&db);
+        let ids = tasks.iter().map(|t| t.task.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["sooner", "later", "orphan"]);
+        assert_eq!(tasks[0].board_title.as_deref(), Some("Default Board"));
+        assert!(!tasks[0].orphaned);
+        assert!(tasks[2].orphaned);
+
+        let value = serde_json::to_value(&tasks[2]).unwrap();
+        assert_eq!(value["id"], "orphan");
+        assert_eq!(value["boardTitle"], serde_json::Value::Null);
+        assert_eq!(value["orphaned"], true);
+    }
+
+    #[test]
+    fn classifies_due_dates() {
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        assert_eq!(classify_due("2024-05-09", today), DueStatus::Overdue);
+        assert_eq!(
+            classify_due("2024-05-10T18:00:00Z", today),
+            DueStatus::DueToday
+        );
+        assert_eq!(classify_due("2024-05-13", today), DueStatus::DueSoon(3));
+        assert_eq!(classify_due("1718000000", today), DueStatus::Later);
+        assert_eq!(classify_due("next tuesday", today), DueStatus::Unparseable);
+        assert_eq!(
+            serde_json::to_value(DueStatus::DueSoon(3)).unwrap(),
+            json!({ "kind": "dueSoon", "days": 3 })
+        );
+
+        let mut db = default_db();
+        let mut typo = sample_task("typo", "Typo", "Inbox");
+        typo.due = Some("2024-13-45".to_string());
+        let mut old = sample_task("old", "Old", "Inbox");
+        old.due = Some("2001-01-01".to_string());
+        db.tasks = vec![typo, old, sample_task("none", "None", "Inbox")];
+
+        let tasks = list_tasks_inner(&db, None);
+        let statuses = tasks
+            .iter()
+            .map(|t| (t.id.as_str(), t.due_status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ("none", None),
+                ("old", Some(DueStatus::Overdue)),
+                ("typo", Some(DueStatus::Unparseable)),
+            ]
+        );
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        assert!(board.columns[0]
+            .tasks
+            .iter()
+            .any(|t| t.due_status == Some(DueStatus::Overdue)));
+        assert!(db.tasks.iter().all(|t| t.due_status.is_none()));
+    }
+
+    #[test]
+    fn sets_and_clears_due_date() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("t1", "Card", "Inbox"));
+
+        let task = set_task_due_inner(&mut db, "t1", Some("2024-05-10T15:30:00+02:00")).unwrap();
+        assert_eq!(task.due.as_deref(), Some("2024-05-10"));
+        assert!(task.updated.is_some());
+
+        assert!(matches!(
+            set_task_due_inner(&mut db, "t1", Some("someday")),
+            Err(VaultError::InvalidData(_))
+        ));
+        assert_eq!(db.tasks[0].due.as_deref(), Some("2024-05-10"));
+
+        set_task_due_inner(&mut db, "t1", None).unwrap();
+        assert_eq!(db.tasks[0].due, None);
+        let value = serde_json::to_value(&db.tasks[0]).unwrap();
+        assert!(value.get("due").is_none());
+    }
+
+    #[test]
+    fn manages_tags_across_tasks() {
+        let mut db = default_db();
+        let mut first = sample_task("t1", "First", "Inbox");
+        first.tags = vec!["api".to_string(), "ui".to_string()];
+        let mut second = sample_task("t2", "Second", "Inbox");
+        second.tags = vec!["API".to_string(), "backend".to_string()];
+        db.tasks = vec![first, second];
+
+        let task = add_task_tag_inner(&mut db, "t1", "urgent", false).unwrap();
+        assert_eq!(task.tags, vec!["api", "ui", "urgent"]);
+        let task = add_task_tag_inner(&mut db, "t1", "api", false).unwrap();
+        assert_eq!(task.tags, vec!["api", "ui", "urgent"]);
+        let task = add_task_tag_inner(&mut db, "t1", "URGENT", true).unwrap();
+        assert_eq!(task.tags, vec!["api", "ui", "urgent"]);
+
+        let task = remove_task_tag_inner(&mut db, "t1", "UI", false).unwrap();
+        assert_eq!(task.tags, vec!["api", "ui", "urgent"]);
+        let task = remove_task_tag_inner(&mut db, "t1", "UI", true).unwrap();
+        assert_eq!(task.tags, vec!["api", "urgent"]);
+        let task = remove_task_tag_inner(&mut db, "t1", " urgent ", false).unwrap();
+        assert_eq!(task.tags, vec!["api"]);
+        assert!(remove_task_tag_inner(&mut db, "t1", "  ", false).is_err());
+
+        assert_eq!(rename_tag_inner(&mut db, "api", "http", false).unwrap(), 1);
+        assert_eq!(db.tasks[1].tags, vec!["API", "backend"]);
+        assert_eq!(
+            rename_tag_inner(&mut db, "api", "backend", true).unwrap(),
+            1
+        );
+        assert_eq!(db.tasks[1].tags, vec!["backend"]);
+    }
+
+    #[test]
+    fn lists_tags_by_count() {
+        let mut db = default_db();
+        assert!(list_tags_inner(&db, None, false).is_empty());
+
+        let tagged = |id: &str, board: &str, tags: &[&str]| {
+            let mut task = sample_task(id, id, "Inbox");
+            task.board = board.to_string();
+            task.tags = tags.iter().map(|t| t.to_string()).collect();
+            task
+        };
+        db.tasks = vec![
+            tagged("t1", "default", &["ui", "api"]),
+            tagged("t2", "default", &["api", "api"]),
+            tagged("t3", "other", &["ops", "ui"]),
+        ];
+        db.archive = vec![tagged("t4", "default", &["legacy"])];
+
+        let count = |tags: Vec<TagCount>| {
+            tags.into_iter()
+                .map(|t| (t.tag, t.count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            count(list_tags_inner(&db, None, false)),
+            vec![
+                ("api".to_string(), 2),
+                ("ui".to_string(), 2),
+                ("ops".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            count(list_tags_inner(&db, Some("default"), true)),
+            vec![
+                ("api".to_string(), 2),
+                ("legacy".to_string(), 1),
+                ("ui".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn bulk_moves_a_column() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("t1", "One", "Review"),
+            sample_task("t2", "Two", "Review"),
+            sample_task("t3", "Three", "Inbox"),
+        ];
+        let payload = BulkMoveColumnPayload {
+            board_id: "default".to_string(),
+            from_column: "Review".to_string(),
+            to_column: "Done".to_string(),
+        };
+
+        let moved = bulk_move_column_inner(&mut db, &payload).unwrap();
+        assert_eq!(moved, vec!["t1".to_string(), "t2".to_string()]);
+        assert!(db.tasks[..2]
+            .iter()
+            .all(|t| t.column == "Done" && t.transitions.len() == 1 && t.updated.is_some()));
+        assert_eq!(db.tasks[2].column, "Inbox");
+
+        let bad = BulkMoveColumnPayload {
+            to_column: "Shipped".to_string(),
+            ..payload
+        };
+        assert!(matches!(
+            bulk_move_column_inner(&mut db, &bad),
+            Err(VaultError::ColumnNotFound(_, _))
+        ));
+    }
+
+    #[test]
+    fn new_vault_uses_configured_columns() {
+        let dir = std::env::temp_dir().join(generate_id("pm-vault-test"));
+        let db_file = dir.join("pm-db.json");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut config = VaultConfig::default();
+        let mut app_config = AppConfig::default();
+        assert_eq!(vault_settings_from(&config).default_columns.len(), 6);
+        let bad = VaultSettings {
+            default_columns: vec!["Todo".to_string(), " Todo ".to_string()],
+            use_slug_ids: false,
+            auto_commit: false,
+        };
+        assert!(apply_vault_settings(&mut config, &mut app_config, bad).is_err());
+        let settings = VaultSettings {
+            default_columns: vec![" Todo".to_string(), "Done".to_string()],
+            use_slug_ids: true,
+            auto_commit: false,
+        };
+        apply_vault_settings(&mut config, &mut app_config, settings).unwrap();
+        assert_eq!(config.use_slug_ids, None);
+        assert_eq!(app_config.use_slug_ids, Some(true));
+        save_config(&dir.join(CONFIG_FILE_NAME), &config).unwrap();
+
+        let db = load_db(&db_file).unwrap();
+        assert_eq!(db.boards[0].columns, vec!["Todo", "Done"]);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn app_settings_override_legacy_vault_config() {
+        let legacy = VaultConfig {
+            openai_api_key: Some("sk-vault".to_string()),
+            use_slug_ids: Some(true),
+            auto_commit: Some(true),
+            ..VaultConfig::default()
+        };
+        let merged = with_app_settings(legacy.clone(), &AppConfig::default());
+        assert_eq!(merged.openai_api_key.as_deref(), Some("sk-vault"));
+        assert_eq!(merged.use_slug_ids, Some(true));
+
+        let app_config = AppConfig {
+            openai_api_key: Some("sk-app".to_string()),
+            auto_commit: Some(false),
+            ..AppConfig::default()
+        };
+        let merged = with_app_settings(legacy, &app_config);
+        assert_eq!(merged.openai_api_key.as_deref(), Some("sk-app"));
+        assert_eq!(merged.use_slug_ids, Some(true));
+        assert_eq!(merged.auto_commit, Some(false));
+
+        // Another vault without its own config still sees the app-wide key.
+        let merged = with_app_settings(VaultConfig::default(), &app_config);
+        assert_eq!(merged.openai_api_key.as_deref(), Some("sk-app"));
+    }
+
+    #[test]
+    fn switches_between_known_vaults() {
+        let default = std::env::temp_dir().join(generate_id("pm-vault-default"));
+        let work = std::env::temp_dir().join(generate_id("pm-vault-work"));
+        fs::create_dir_all(&default).unwrap();
+
+        assert!(prepare_vault_dir("relative/vault").is_err());
+        let dir = prepare_vault_dir(&work.to_string_lossy()).unwrap();
+        assert!(dir.join("pm-db.json").is_file());
+
+        let mut config = AppConfig::default();
+        remember_vault(&mut config, &dir);
+        remember_vault(&mut config, &dir);
+        assert_eq!(config.known_vaults.len(), 1);
+        assert_eq!(resolve_vault_dir(default.clone(), &config), work);
+
+        let vaults = known_vaults_inner(&default, &work, &config);
+        assert_eq!(vaults.len(), 2);
+        assert!(!vaults[0].active && vaults[1].active && vaults[1].exists);
+
+        fs::remove_dir_all(&work).unwrap();
+        assert_eq!(resolve_vault_dir(default.clone(), &config), default);
+        let _ = fs::remove_dir_all(default);
+    }
+
+    #[test]
+    fn dependencies_reject_self_references_and_cycles() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("build", "Build", "Done"),
+            sample_task("test", "Test", "Ready"),
+            sample_task("deploy", "Deploy", "Backlog"),
+        ];
+
+        add_dependency_inner(&mut db, "test", "build").unwrap();
+        let deploy = add_dependency_inner(&mut db, "deploy", "test").unwrap();
+        assert_eq!(deploy.blocked_by, vec!["test".to_string()]);
+        add_dependency_inner(&mut db, "deploy", "test").unwrap();
+        assert_eq!(db.tasks[2].blocked_by.len(), 1);
+
+        assert!(add_dependency_inner(&mut db, "test", "test").is_err());
+        assert!(matches!(
+            add_dependency_inner(&mut db, "test", "missing"),
+            Err(VaultError::TaskNotFound(_))
+        ));
+        assert!(matches!(
+            add_dependency_inner(&mut db, "build", "deploy"),
+            Err(VaultError::DependencyCycle(_))
+        ));
+
+        let blocked = blocked_tasks_inner(&db, Some("default"));
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].task.id, "deploy");
+        assert_eq!(blocked[0].open_blockers, vec!["test".to_string()]);
+
+        remove_dependency_inner(&mut db, "deploy", "test").unwrap();
+        assert!(blocked_tasks_inner(&db, None).is_empty());
+    }
+
+    #[test]
+    fn activity_log_reads_newest_first_and_skips_torn_lines() {
+        let path = std::env::temp_dir().join(format!("{}.log", generate_id("pm-activity-test")));
+        assert!(read_activity_log(&path, 10).unwrap().is_empty());
+
+        for id in ["t1", "t2", "t3"] {
+            let entry = ActivityEntry {
+                timestamp: now_iso(),
+                action: "create".to_string(),
+                entity_type: "task".to_string(),
+                id: id.to_string(),
+                details: json!({ "title": id }),
+            };
+            append_activity(&path, &entry).unwrap();
+        }
+        let mut raw = fs::read_to_string(&path).unwrap();
+        raw.push_str(r#"{"timestamp":"2024-01-01T00:00:00Z","act"#);
+        fs::write(&path, raw).unwrap();
+
+        let entries = read_activity_log(&path, 2).unwrap();
+        let ids = entries.iter().map(|e| e.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["t3", "t2"]);
+        assert_eq!(entries[0].details["title"], "t3");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn cycle_time_sums_column_visits() {
+        let mut task = sample_task("t1", "One", "Done");
+        task.created = Some("2024-01-01T00:00:00Z".to_string());
+        task.transitions = vec![
+            Transition {
+                from: "Backlog".to_string(),
+                to: "In Progress".to_string(),
+                at: "2024-01-01T01:00:00Z".to_string(),
+            },
+            Transition {
+                from: "In Progress".to_string(),
+                to: "Backlog".to_string(),
+                at: "2024-01-01T03:00:00Z".to_string(),
+            },
+            Transition {
+                from: "Backlog".to_string(),
+                to: "Done".to_string(),
+                at: "2024-01-01T04:00:00Z".to_string(),
+            },
+        ];
+        let now = parse_instant("2024-01-01T05:00:00Z").unwrap();
+
+        let cycle = task_cycle_time_inner(&task, &default_done_columns(), now);
+        let spent = cycle
+            .columns
+            .iter()
+            .map(|c| (c.column.as_str(), c.seconds, c.unknown))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spent,
+            vec![
+                ("Backlog", 7200, false),
+                ("In Progress", 7200, false),
+                ("Done", 3600, false),
+            ]
+        );
+        assert_eq!(cycle.done_at.as_deref(), Some("2024-01-01T04:00:00Z"));
+        assert_eq!(cycle.total_seconds, Some(14400));
+    }
+
+    #[test]
+    fn cycle_time_without_history_uses_created_and_updated() {
+        let now = parse_instant("2024-01-03T00:00:00Z").unwrap();
+        let mut task = sample_task("t1", "One", "Done");
+        task.created = Some("1704067200".to_string());
+        task.updated = Some("2024-01-02T00:00:00Z".to_string());
+        let cycle = task_cycle_time_inner(&task, &default_done_columns(), now);
+        assert_eq!(cycle.total_seconds, Some(86400));
+
+        task.created = None;
+        task.column = "Review".to_string();
+        let cycle = task_cycle_time_inner(&task, &default_done_columns(), now);
+        assert_eq!(cycle.columns.len(), 1);
+        assert!(cycle.columns[0].unknown);
+        assert_eq!((cycle.done_at, cycle.total_seconds), (None, None));
+    }
+
+    #[test]
+    fn throughput_counts_arrivals_in_done_per_day() {
+        let mut db = default_db();
+        let mut shipped = sample_task("t1", "One", "Done");
+        shipped.transitions = vec![Transition {
+            from: "Review".to_string(),
+            to: "Done".to_string(),
+            at: "2024-03-02T10:00:00Z".to_string(),
+        }];
+        let mut legacy = sample_task("t2", "Two", "Done");
+        legacy.updated = Some("2024-03-01T08:00:00Z".to_string());
+        let mut archived = sample_task("t3", "Three", "Done");
+        archived.updated = Some("2024-03-02T09:00:00Z".to_string());
+        let mut outside = sample_task("t4", "Four", "Done");
+        outside.updated = Some("2024-04-01T00:00:00Z".to_string());
+        db.tasks = vec![shipped, legacy, outside];
+        db.archive = vec![archived];
+
+        let from = parse_day("2024-03-01").unwrap();
+        let to = parse_day("2024-03-03").unwrap();
+        let throughput = board_throughput_inner(&db, "default", from, to).unwrap();
+        let counts = throughput.days.iter().map(|d| d.count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 2, 0]);
+        assert_eq!(throughput.days[0].date, "2024-03-01");
+        assert_eq!(throughput.total, 3);
+
+        assert!(board_throughput_inner(&db, "default", to, from).is_err());
+        assert!(parse_day("March 1").is_err());
+    }
+
+    #[test]
+    fn update_board_relocates_tasks_from_removed_columns() {
+        let mut db = default_db();
+        db.boards[0].wip_limits.insert("Review".to_string(), 2);
+        db.tasks = vec![
+            sample_task("t1", "One", "Review"),
+            sample_task("t2", "Two", "Done"),
+        ];
+
+        let reorder = UpdateBoardPayload {
+            board_id: "default".to_string(),
+            title: Some(" Team Board ".to_string()),
+            columns: Some(vec!["Done".to_string(), "Review".to_string()]),
+        };
+        assert!(update_board_inner(&mut db, reorder.clone())
+            .unwrap()
+            .is_empty());
+        assert_eq!(db.boards[0].title, "Team Board");
+        assert!(db.tasks.iter().all(|t| t.transitions.is_empty()));
+
+        let shrink = UpdateBoardPayload {
+            title: None,
+            columns: Some(vec!["Todo".to_string(), "Done".to_string()]),
+            ..reorder
+        };
+        assert_eq!(update_board_inner(&mut db, shrink).unwrap(), vec!["t1"]);
+        assert_eq!(db.tasks[0].column, "Todo");
+        assert!(db.tasks[0].updated.is_some());
+        assert!(db.boards[0].wip_limits.is_empty());
+        assert_eq!(db.boards[0].done_columns, vec!["Done"]);
+
+        db.boards[0].done_columns.push("Todo".to_string());
+        let drop_done = UpdateBoardPayload {
+            board_id: "default".to_string(),
+            title: None,
+            columns: Some(vec!["Todo".to_string(), "Shipped".to_string()]),
+        };
+        update_board_inner(&mut db, drop_done).unwrap();
+        assert_eq!(db.boards[0].done_columns, vec!["Todo"]);
+
+        let empty = UpdateBoardPayload {
+            board_id: "default".to_string(),
+            title: None,
+            columns: Some(vec![]),
+        };
+        assert!(update_board_inner(&mut db, empty).is_err());
+    }
+
+    #[test]
+    fn vault_round_trip_in_a_temp_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+
+        let mut db = load_db(&path).unwrap();
+        let created = create_story_inner(&mut db, story_payload("Round trip"), false).unwrap();
+        let body = "\n\n  indented first line\n\n- keep\n  - nesting\n\n\n";
+        db.tasks[0].body = body.to_string();
+        save_db(&path, &db).unwrap();
+
+        let mut db = load_db(&path).unwrap();
+        let listed = list_tasks_inner(&db, Some("default"));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].title, "Round trip");
+        assert_eq!(listed[0].column, "Inbox");
+
+        update_task_column_inner(&mut db, &created.id, "Review", false).unwrap();
+        save_db(&path, &db).unwrap();
+
+        let db = load_db(&path).unwrap();
+        let task = get_task_inner(&db, &created.id).unwrap();
+        assert_eq!(task.column, "Review");
+        assert_eq!(task.transitions.len(), 1);
+        assert_eq!(task.body, body);
+    }
+
+    #[test]
+    fn vault_dir_override_reads_the_environment() {
+        let env = |value: &'static str| {
+            move |key: &str| (key == VAULT_DIR_ENV).then(|| value.to_string())
+        };
+        assert_eq!(
+            vault_dir_override_from(env("/srv/scratch-vault")),
+            Some(PathBuf::from("/srv/scratch-vault"))
+        );
+        assert_eq!(vault_dir_override_from(env(" ")), None);
+        assert_eq!(vault_dir_override_from(|_| None), None);
+    }
+
+    #[test]
+    fn load_db_rereads_the_file_after_it_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+        let mut db = load_db(&path).unwrap();
+        db.tasks.push(sample_task("t1", "One", "Inbox"));
+        save_db(&path, &db).unwrap();
+        assert_eq!(load_db(&path).unwrap().tasks.len(), 1);
+
+        // An outside edit changes the size, so the cached copy is dropped.
+        let mut edited = db.clone();
+        edited.tasks.push(sample_task("t2", "Two", "Inbox"));
+        fs::write(&path, serde_json::to_string(&edited).unwrap()).unwrap();
+        assert_eq!(load_db(&path).unwrap().tasks.len(), 2);
+
+        clear_db_cache();
+        assert_eq!(load_db(&path).unwrap().tasks.len(), 2);
+    }
+
+    #[test]
+    fn load_db_recreates_a_deleted_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+        ensure_db(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // Already checked once, so this is a no-op until the file is found missing.
+        ensure_db(&path).unwrap();
+        assert!(!path.exists());
+
+        assert_eq!(load_db(&path).unwrap().boards.len(), 1);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn slug_ids_are_safe_and_unique() {
+        assert_eq!(slugify("Fix login bug"), "fix-login-bug");
+        assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify("  Ünïcode: déjà vu!  "), "n-code-d-j-vu");
+        assert_eq!(slugify("???"), "");
+
+        let mut db = default_db();
+        let first = create_story_inner(&mut db, story_payload("Fix login bug"), true).unwrap();
+        let second = create_story_inner(&mut db, story_payload("Fix login bug!"), true).unwrap();
+        assert_eq!(first.id, "fix-login-bug");
+        assert_eq!(second.id, "fix-login-bug-2");
+        assert!(new_entity_id(&db, "story", "???", true).starts_with("story-"));
+        assert!(new_entity_id(&db, "story", "Fix login bug", false).starts_with("story-"));
+    }
+
+    #[test]
+    fn sanitize_id_rejects_path_tricks() {
+        assert_eq!(sanitize_id("story-123").unwrap(), "story-123");
+        for bad in ["../foo", "a/b", "a\\b", ".hidden", "", "a..b", "tab\there"] {
+            assert!(
+                matches!(sanitize_id(bad), Err(VaultError::InvalidId(_, _))),
+                "{bad:?} should be rejected"
+            );
+        }
+
+        let mut bundle = export_vault_inner(&default_db());
+        let mut task = sample_task("../../evil", "Evil", "Inbox");
+        task.board = "default".to_string();
+        bundle.tasks.push(task);
+        let mut db = default_db();
+        assert!(import_vault_inner(&mut db, bundle, ImportMode::Skip).is_err());
+        assert!(db.tasks.is_empty());
+    }
+
+    #[test]
+    fn errors_cross_ipc_with_a_stable_code() {
+        let response = ErrorResponse::from(VaultError::BoardNotFound("b1".to_string()));
+        assert_eq!(response.code, "board_not_found");
+        assert_eq!(response.message, "board not found: b1");
+        assert_eq!(
+            serde_json::to_value(ErrorResponse::from(VaultError::OpenAiKeyMissing)).unwrap()
+                ["code"],
+            "openai_key_missing"
+        );
+    }
+
+    #[test]
+    fn validate_vault_reports_dangling_references() {
+        let mut db = default_db();
+        db.projects = vec![sample_project("p1")];
+        db.epics = vec![
+            sample_epic("e1", Some("p1")),
+            sample_epic("e2", Some("gone")),
+        ];
+        let mut linked = sample_task("t1", "Linked", "Inbox");
+        linked.project_id = Some("p1".to_string());
+        linked.epic_id = Some("e9".to_string());
+        let mut lost = sample_task("t2", "Lost", "Nowhere");
+        lost.board = "missing".to_string();
+        db.tasks = vec![linked, lost, sample_task("t3", "Stray", "Shipped")];
+
+        let mut value = serde_json::to_value(&db).unwrap();
+        value["tasks"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "id": "t4", "title": 7 }));
+
+        let issues = validate_vault_inner(&value.to_string());
+        let found = issues
+            .iter()
+            .map(|i| (i.entity_type.as_str(), i.id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("task", "t4"),
+                ("task", "t1"),
+                ("task", "t2"),
+                ("task", "t3"),
+                ("epic", "e2"),
+            ]
+        );
+        assert_eq!(issues[1].problem, "epic not found: e9");
+        assert!(issues[0].problem.starts_with("unreadable"));
+
+        assert_eq!(validate_vault_inner("{").len(), 1);
+
+        let mut stale = sample_task("a1", "Old", "Removed column");
+        stale.epic_id = Some("e9".to_string());
+        let mut orphaned = sample_task("a2", "Older", "Done");
+        orphaned.board = "missing".to_string();
+        db.tasks.clear();
+        db.epics.clear();
+        db.archive = vec![stale, orphaned];
+        let issues = validate_vault_inner(&serde_json::to_string(&db).unwrap());
+        let found = issues
+            .iter()
+            .map(|i| (i.entity_type.as_str(), i.id.as_str(), i.problem.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("archived_task", "a1", "epic not found: e9"),
+                ("archived_task", "a2", "board not found: missing"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repairs_tasks_in_unknown_columns() {
+        let mut db = default_db();
+        let mut orphan = sample_task("t3", "Orphan", "Gone");
+        orphan.board = "missing".to_string();
+        db.tasks = vec![
+            sample_task("t1", "Lost", "Doing"),
+            sample_task("t2", "Fine", "Review"),
+            orphan,
+        ];
+
+        let mut payload = RepairTaskColumnsPayload {
+            board_id: None,
+            fallback_column: None,
+            dry_run: Some(true),
+        };
+        let planned = repair_task_columns_inner(&mut db, &payload).unwrap();
+        assert_eq!(
+            planned,
+            vec![RepairedTask {
+                task_id: "t1".to_string(),
+                board: "default".to_string(),
+                from: "Doing".to_string(),
+                to: "Inbox".to_string(),
+            }]
+        );
+        assert_eq!(db.tasks[0].column, "Doing");
+
+        payload.fallback_column = Some("Nope".to_string());
+        assert!(repair_task_columns_inner(&mut db, &payload).is_err());
+
+        payload.fallback_column = Some("Ready".to_string());
+        payload.dry_run = None;
+        repair_task_columns_inner(&mut db, &payload).unwrap();
+        assert_eq!(db.tasks[0].column, "Ready");
+        assert!(db.tasks[0].updated.is_some());
+        assert_eq!(db.tasks[2].column, "Gone");
+    }
+
+    #[test]
+    fn swimlanes_group_tasks_by_a_field() {
+        let mut db = default_db();
+        let mut a = sample_task("t1", "One", "Inbox");
+        a.owner = Some("sam".to_string());
+        a.epic_id = Some("e1".to_string());
+        let mut b = sample_task("t2", "Two", "Done");
+        b.owner = Some("alex".to_string());
+        let mut c = sample_task("t3", "Three", "Inbox");
+        c.owner = Some("sam".to_string());
+        db.tasks = vec![a, b, c, sample_task("t4", "Four", "Review")];
+
+        let lanes = board_swimlanes_inner(&db, "default", "owner").unwrap();
+        let names = lanes
+            .lanes
+            .iter()
+            .map(|l| l.lane.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["alex", "sam", "Ungrouped"]);
+        let sam = &lanes.lanes[1];
+        assert_eq!(sam.columns.len(), 6);
+        assert_eq!(sam.columns[0].tasks.len(), 2);
+
+        let by_epic = board_swimlanes_inner(&db, "default", "epic_id").unwrap();
+        assert_eq!(by_epic.lanes[0].lane, "e1");
+        assert_eq!(by_epic.lanes[1].lane, UNGROUPED_LANE);
+        assert!(board_swimlanes_inner(&db, "missing", "owner").is_err());
+    }
+
+    #[test]
+    fn custom_task_fields_survive_a_round_trip() {
+        let mut value = serde_json::to_value(sample_task("t1", "One", "Inbox")).unwrap();
+        value["estimate"] = json!(3);
+        value["risk"] = json!("high");
+        value["dueStatus"] = json!({ "kind": "overdue" });
+        let mut db = default_db();
+        db.tasks = vec![serde_json::from_value(value).unwrap()];
+        assert_eq!(db.tasks[0].custom.len(), 2);
+
+        update_task_column_inner(&mut db, "t1", "Review", false).unwrap();
+        let written = serde_json::to_value(&db.tasks[0]).unwrap();
+        assert_eq!(written["estimate"], 3);
+        assert_eq!(written["risk"], "high");
+        assert_eq!(written["column"], "Review");
+        assert!(written.get("dueStatus").is_none());
+    }
+
+    #[test]
+    fn columns_sort_by_priority_with_unset_last() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("t1", "Alpha", "Inbox"),
+            sample_task("t2", "Bravo", "Inbox"),
+            sample_task("t3", "Charlie", "Inbox"),
+        ];
+        set_task_priority_inner(&mut db, "t1", Some(Priority::Low)).unwrap();
+        set_task_priority_inner(&mut db, "t3", Some(Priority::Urgent)).unwrap();
+
+        let ids = |sort| {
+            board_with_sorted_tasks_inner(&db, "default", sort)
+                .unwrap()
+                .columns[0]
+                .tasks
+                .iter()
+                .map(|t| t.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(TaskSort::Priority), vec!["t3", "t1", "t2"]);
+        assert_eq!(ids(TaskSort::Title), vec!["t1", "t2", "t3"]);
+
+        let stored = serde_json::to_value(&db.tasks[2]).unwrap();
+        assert_eq!(stored["priority"], "urgent");
+        let mut value = stored;
+        value["priority"] = json!(" HIGH ");
+        let parsed: Task = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.priority, Some(Priority::High));
+
+        // Values we don't recognise survive a load/save round trip and sort below `low`.
+        value["priority"] = json!("P1");
+        let parsed: Task = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.priority, Some(Priority::Other(json!("P1"))));
+        assert_eq!(serde_json::to_value(&parsed).unwrap()["priority"], "P1");
+        value["priority"] = json!(2);
+        let parsed: Task = serde_json::from_value(value).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap()["priority"], 2);
+        assert!(Priority::Other(json!("P1")) < Priority::Low);
+
+        db.tasks[1].priority = Some(Priority::Other(json!("P1")));
+        let sorted = board_with_sorted_tasks_inner(&db, "default", TaskSort::Priority).unwrap();
+        let sorted = sorted.columns[0].tasks.iter().map(|t| t.id.as_str());
+        assert_eq!(sorted.collect::<Vec<_>>(), vec!["t3", "t1", "t2"]);
+        assert!(
+            set_task_priority_inner(&mut db, "t2", Some(Priority::Other(json!("P1")))).is_err()
+        );
+    }
+
+    #[test]
+    fn create_stories_validates_the_whole_batch_first() {
+        let mut db = default_db();
+        let mut bad_column = story_payload("Third");
+        bad_column.column = Some("Shipped".to_string());
+        let batch = vec![story_payload("First"), story_payload("  "), bad_column];
+
+        let err = create_stories_inner(&mut db, batch, false)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("#2: invalid data: title cannot be empty"),
+            "{err}"
+        );
+        assert!(err.contains("#3: column not found"), "{err}");
+        assert!(db.tasks.is_empty());
+
+        let batch = (0..50)
+            .map(|i| story_payload(&format!("Story {i}")))
+            .collect::<Vec<_>>();
+        let created = create_stories_inner(&mut db, batch, false).unwrap();
+        let mut ids = created.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 50);
+        assert_eq!(db.tasks.len(), 50);
+    }
+
+    #[test]
+    fn imports_stories_from_csv_with_row_errors() {
+        let mut db = default_db();
+        let csv_text = "Title,Column,Tags,Due,Description\n\
+            \"Fix login, again\",Ready,auth;bug,2024-05-01,\"Users see \"\"500\"\"\"\n\
+            ,Ready,,,\n\
+            Bad due,Backlog,,someday,\n\
+            Plain\n";
+
+        let summary = import_stories_csv_inner(&mut db, csv_text, "default", false).unwrap();
+        assert_eq!(summary.created.len(), 2);
+        let lines = summary.errors.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![3, 4]);
+
+        let fixed = &db.tasks[0];
+        assert_eq!(fixed.title, "Fix login, again");
+        assert_eq!(fixed.column, "Ready");
+        assert_eq!(fixed.tags, vec!["story", "auth", "bug"]);
+        assert_eq!(fixed.due.as_deref(), Some("2024-05-01"));
+        assert_eq!(fixed.body, "Users see \"500\"");
+        assert_eq!(db.tasks[1].column, "Inbox");
+
+        assert!(import_stories_csv_inner(&mut db, "name\nx\n", "default", false).is_err());
+    }
+
+    #[test]
+    fn exports_due_dates_as_all_day_events() {
+        let mut due = sample_task("t1", "Ship, then celebrate; really", "Ready");
+        due.due = Some("2024-12-31".to_string());
+        let mut long = sample_task("t2", &"é".repeat(60), "Ready");
+        long.due = Some("2024-06-01T09:30".to_string());
+        let undated = sample_task("t3", "Someday", "Backlog");
+        let stamp = parse_instant("2024-01-02T03:04:05Z").unwrap();
+
+        let ics = render_due_ics(&[due, long, undated], stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("UID:t1@kanban-vault\r\nDTSTAMP:20240102T030405Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20241231\r\nDTEND;VALUE=DATE:20250101\r\n"));
+        assert!(ics.contains("SUMMARY:Ship\\, then celebrate\\; really\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240601\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains("\r\n é"));
+    }
+
+    #[test]
+    fn commits_vault_into_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = dir.path();
+        fs::write(vault.join("pm-db.json"), "{}").unwrap();
+        let err = git_commit_in(vault, "first").unwrap_err();
+        assert_eq!(err.code(), "git");
+
+        let git = |args: &[&str]| run_git(vault, args).unwrap();
+        assert!(git(&["init", "-q"]).status.success());
+        git(&["config", "user.email", "vault@example.com"]);
+        git(&["config", "user.name", "Vault"]);
+
+        let hash = git_commit_in(vault, "first").unwrap();
+        assert_eq!(hash.len(), 40);
+        let log = git(&["log", "-1", "--format=%H %s"]);
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            format!("{hash} first")
+        );
+
+        let err = git_commit_in(vault, "again").unwrap_err();
+        assert!(err.to_string().contains("nothing to commit"));
+        fs::write(vault.join("pm-db.json"), "{\"boards\":[]}").unwrap();
+        assert_ne!(git_commit_in(vault, "again").unwrap(), hash);
+    }
+
+    #[test]
+    fn batches_auto_commits_per_vault() {
+        assert_eq!(
+            auto_commit_message(&["create task a".to_string()]),
+            "create task a"
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let vault = dir.path();
+        let git = |args: &[&str]| run_git(vault, args).unwrap();
+        assert!(git(&["init", "-q"]).status.success());
+        git(&["config", "user.email", "vault@example.com"]);
+        git(&["config", "user.name", "Vault"]);
+
+        fs::write(vault.join("pm-db.json"), "{}").unwrap();
+        for id in ["a", "b", "c"] {
+            queue_auto_commit(vault, format!("create task {id}"));
+        }
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while PENDING_COMMITS.lock().unwrap().contains_key(vault) {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "auto-commit never ran"
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let count = git(&["rev-list", "--count", "HEAD"]);
+        assert_eq!(String::from_utf8_lossy(&count.stdout).trim(), "1");
+        let log = git(&["log", "-1", "--format=%s"]);
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            "create task a (+2 more)"
+        );
+    }
+
+    #[test]
+    fn materializes_recurring_tasks_once_per_period() {
+        let day = |raw: &str| parse_day(raw).unwrap();
+        assert!(parse_recurrence("hourly").is_err());
+        assert!(parse_recurrence("monthly:32").is_err());
+        assert_eq!(
+            parse_recurrence("Weekly:MON")
+                .unwrap()
+                .latest_occurrence(day("2024-05-01")),
+            day("2024-04-29")
+        );
+        assert_eq!(
+            parse_recurrence("monthly:31")
+                .unwrap()
+                .latest_occurrence(day("2024-03-15")),
+            day("2024-02-29")
+        );
+
+        let mut db = default_db();
+        let mut standup = sample_task("standup", "Standup", "Done");
+        standup.checklist = vec![ChecklistItem {
+            text: "Notes".to_string(),
+            done: true,
+        }];
+        db.tasks.push(standup);
+        set_task_recurrence_inner(&mut db, "standup", Some(" Daily ".to_string())).unwrap();
+        assert!(
+            set_task_recurrence_inner(&mut db, "standup", Some("sometimes".to_string())).is_err()
+        );
+
+        let created = materialize_recurring_inner(&mut db, day("2024-05-01"), false).unwrap();
+        assert_eq!(created.len(), 1);
+        let instance = &created[0];
+        assert_eq!(instance.column, db.boards[0].columns[0]);
+        assert_eq!(instance.due.as_deref(), Some("2024-05-01"));
+        assert_eq!(instance.recurrence_of.as_deref(), Some("standup"));
+        assert!(instance.recurrence.is_none());
+        assert!(!instance.checklist[0].done);
+
+        let instance_id = instance.id.clone();
+        db.tasks
+            .iter_mut()
+            .find(|t| t.id == instance_id)
+            .unwrap()
+            .column = "Done".to_string();
+        assert!(
+            materialize_recurring_inner(&mut db, day("2024-05-01"), false)
+                .unwrap()
+                .is_empty()
+        );
+        archive_task_inner(&mut db, &instance_id).unwrap();
+        assert!(
+            materialize_recurring_inner(&mut db, day("2024-05-01"), false)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            materialize_recurring_inner(&mut db, day("2024-05-02"), false)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(db.tasks.iter().filter(|t| t.id == "standup").count(), 1);
+        assert_eq!((db.tasks.len(), db.archive.len()), (2, 1));
+    }
+
+    #[test]
+    fn links_and_unlinks_tasks_from_epics() {
+        let mut db = default_db();
+        db.epics = vec![sample_epic("e1", None)];
+        db.tasks.push(sample_task("t1", "Legacy card", "Backlog"));
+
+        let err = link_task_to_epic_inner(&mut db, "t1", "missing").unwrap_err();
+        assert_eq!(err.code(), "epic_not_found");
+        assert!(link_task_to_epic_inner(&mut db, "nope", "e1").is_err());
+
+        let linked = link_task_to_epic_inner(&mut db, "t1", "e1").unwrap();
+        assert_eq!(linked.epic_id.as_deref(), Some("e1"));
+        assert!(linked.updated.is_some());
+
+        let unlinked = unlink_task_from_epic_inner(&mut db, "t1").unwrap();
+        assert_eq!(unlinked.epic_id, None);
+        let raw = serde_json::to_value(&unlinked).unwrap();
+        assert!(raw.get("epicId").is_none());
+    }
+
+    #[test]
+    fn builds_project_tree() {
+        let mut db = default_db();
+        db.tasks.clear();
+        let mut beta = sample_project("p2");
+        beta.title = "Beta".to_string();
+        let mut alpha = sample_project("p1");
+        alpha.title = "Alpha".to_string();
+        db.projects = vec![beta, alpha];
+        db.epics = vec![
+            sample_epic("e1", Some("p1")),
+            sample_epic("e2", Some("gone")),
+        ];
+        let mut in_epic_b = sample_task("t1", "B story", "Backlog");
+        in_epic_b.epic_id = Some("e1".to_string());
+        let mut in_epic_a = sample_task("t2", "A story", "Backlog");
+        in_epic_a.epic_id = Some("e1".to_string());
+        let mut direct = sample_task("t3", "Direct", "Backlog");
+        direct.project_id = Some("p2".to_string());
+        direct.epic_id = Some("missing".to_string());
+        let mut lost = sample_task("t4", "Lost", "Backlog");
+        lost.project_id = Some("missing".to_string());
+        db.tasks = vec![in_epic_b, in_epic_a, direct, lost];
+
+        let tree = project_tree_inner(&db);
+        let ids = |tasks: &[Task]| tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        assert_eq!(tree.projects[0].project.id, "p1");
+        assert_eq!(tree.projects[0].epics.len(), 1);
+        assert_eq!(ids(&tree.projects[0].epics[0].stories), vec!["t2", "t1"]);
+        assert_eq!(ids(&tree.projects[1].stories), vec!["t3"]);
+        assert_eq!(tree.unassigned_epics[0].epic.id, "e2");
+        assert_eq!(ids(&tree.orphans), vec!["t4"]);
+    }
+
+    #[test]
+    fn pages_and_sorts_task_lists() {
+        let mut db = default_db();
+        db.tasks.clear();
+        for (id, title, created) in [
+            ("t1", "Charlie", Some("2024-01-03T00:00:00Z")),
+            ("t2", "Alpha", Some("2024-01-01T00:00:00Z")),
+            ("t3", "Bravo", None),
+        ] {
+            let mut task = sample_task(id, title, "Backlog");
+            task.created = created.map(str::to_string);
+            db.tasks.push(task);
+        }
+
+        let all = list_tasks_page_inner(&db, None, None, None, None);
+        assert_eq!(all.total, 3);
+        let titles = all
+            .items
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Alpha", "Bravo", "Charlie"]);
+
+        let page = list_tasks_page_inner(&db, None, None, Some(1), Some(1));
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].title, "Bravo");
+
+        let newest = list_tasks_page_inner(&db, None, Some(TaskSort::Created), None, None);
+        let ids = newest
+            .items
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["t1", "t2", "t3"]);
+
+        let past_end = list_tasks_page_inner(&db, Some("default"), None, Some(10), Some(5));
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 3);
+    }
+
+    #[test]
+    fn counts_tasks_per_column() {
+        let mut db = default_db();
+        db.tasks = vec![
+            sample_task("t1", "One", "Backlog"),
+            sample_task("t2", "Two", "Backlog"),
+            sample_task("t3", "Three", "Nowhere"),
+        ];
+        let counts = column_counts_inner(&db, "default").unwrap();
+        assert_eq!(counts.len(), db.boards[0].columns.len());
+        assert_eq!(counts["Backlog"], 2);
+        assert_eq!(counts["Done"], 0);
+        assert!(!counts.contains_key("Nowhere"));
+        assert_eq!(
+            column_counts_inner(&db, "missing").unwrap_err().code(),
+            "board_not_found"
+        );
+    }
+
+    #[test]
+    fn boards_choose_their_done_columns() {
+        let legacy: Board =
+            serde_json::from_str(r#"{"id":"b","title":"B","columns":["Todo","Done"]}"#).unwrap();
+        assert_eq!(legacy.done_columns, vec!["Done"]);
+
+        let mut db = default_db();
+        let mut board = sample_board("ops", &["Todo", "Shipped", "Closed"]);
+        board.done_columns = vec!["Shipped".to_string(), "Closed".to_string()];
+        db.boards.push(board);
+        db.epics = vec![sample_epic("e1", None)];
+        let mut shipped = sample_task("t1", "Shipped", "Shipped");
+        shipped.board = "ops".to_string();
+        shipped.epic_id = Some("e1".to_string());
+        shipped.transitions = vec![Transition {
+            from: "Todo".to_string(),
+            to: "Shipped".to_string(),
+            at: "2024-03-02T10:00:00Z".to_string(),
+        }];
+        let mut open = sample_task("t2", "Open", "Todo");
+        open.board = "ops".to_string();
+        open.epic_id = Some("e1".to_string());
+        open.blocked_by = vec!["t1".to_string()];
+        db.tasks = vec![shipped, open];
+
+        assert_eq!(epic_progress_inner(&db, "e1", None).unwrap().done, 1);
+        assert!(blocked_tasks_inner(&db, Some("ops")).is_empty());
+        let day = parse_day("2024-03-02").unwrap();
+        assert_eq!(
+            board_throughput_inner(&db, "ops", day, day).unwrap().total,
+            1
+        );
+    }
+
+    #[test]
+    fn concurrent_locked_updates_all_survive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+        let mut db = load_db(&path).unwrap();
+        db.tasks = vec![sample_task("t1", "Shared", "Backlog")];
+        save_db(&path, &db).unwrap();
+
+        let rounds = 15;
+        let checklist_path = path.clone();
+        let checklist = std::thread::spawn(move || {
+            for i in 0..rounds {
+                add_checklist_item_at(&checklist_path, "t1", &format!("step {i}")).unwrap();
+            }
+        });
+        let mover_path = path.clone();
+        let mover = std::thread::spawn(move || {
+            for i in 0..rounds {
+                let payload = UpdateTaskColumnPayload {
+                    task_id: "t1".to_string(),
+                    column: if i % 2 == 0 { "Ready" } else { "Backlog" }.to_string(),
+                    force: None,
+                };
+                update_task_column_at(&mover_path, &payload).unwrap();
+            }
+        });
+        checklist.join().unwrap();
+        mover.join().unwrap();
+
+        clear_db_cache();
+        let task = get_task_inner(&load_db(&path).unwrap(), "t1").unwrap();
+        assert_eq!(task.checklist.len(), rounds);
+        assert_eq!(task.transitions.len(), rounds);
+
+        let _held = lock_db_file(&path).unwrap();
+        let err = add_checklist_item_at(&path, "t1", "blocked").unwrap_err();
+        assert_eq!(err.code(), "file_busy");
+    }
+
+    #[test]
+    fn summarizes_vault_stats() {
+        let mut db = default_db();
+        db.boards.push(sample_board("ops", &["Todo", "Done"]));
+        db.projects = vec![sample_project("p1")];
+        db.epics = vec![sample_epic("e1", Some("p1")), sample_epic("e2", None)];
+        let mut late = sample_task("t1", "Late", "Backlog");
+        late.due = Some("2000-01-01".to_string());
+        let mut finished_late = sample_task("t2", "Finished", "Done");
+        finished_late.due = Some("2000-01-01".to_string());
+        let mut ops = sample_task("t3", "Ops", "Todo");
+        ops.board = "ops".to_string();
+        db.tasks = vec![late, finished_late, ops];
+
+        let stats = vault_stats_inner(&db);
+        assert_eq!(
+            (stats.boards, stats.tasks, stats.projects, stats.epics),
+            (2, 3, 1, 2)
+        );
+        assert_eq!(stats.tasks_per_board["default"], 2);
+        assert_eq!(stats.tasks_per_board["ops"], 1);
+        assert_eq!(stats.tasks_per_column["Done"], 1);
+        assert_eq!(stats.tasks_per_column["Review"], 0);
+        assert_eq!(stats.tasks_per_column["Todo"], 1);
+        assert_eq!(stats.overdue, 1);
+    }
+
+    #[test]
+    fn expands_tilde_and_env_vars_in_paths() {
+        let env = |key: &str| match key {
+            "HOME" => Some("/home/sam".to_string()),
+            "VAULTS" => Some("/srv/vaults".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        let expand = |raw: &str| expand_path_with(raw, env);
+        assert_eq!(expand("~").unwrap(), PathBuf::from("/home/sam"));
+        assert_eq!(
+            expand(" ~/Documents/vault ").unwrap(),
+            PathBuf::from("/home/sam/Documents/vault")
+        );
+        assert_eq!(
+            expand("$VAULTS/work").unwrap(),
+            PathBuf::from("/srv/vaults/work")
+        );
+        assert_eq!(
+            expand("${VAULTS}-old/$").unwrap(),
+            PathBuf::from("/srv/vaults-old/$")
+        );
+        assert_eq!(expand("/tmp/~x").unwrap(), PathBuf::from("/tmp/~x"));
+
+        assert!(expand("$UNSET/x").is_err());
+        assert!(expand("${VAULTS").is_err());
+        assert!(expand("/tmp/a\0b").is_err());
+        assert!(expand("$EMPTY").is_err());
+        assert!(expand_path_with("~/x", |_| None).is_err());
+        assert_eq!(
+            vault_dir_override_from(|key| match key {
+                VAULT_DIR_ENV => Some("$VAULTS/scratch".to_string()),
+                _ => env(key),
+            }),
+            Some(PathBuf::from("/srv/vaults/scratch"))
+        );
+    }
+
+    #[test]
+    fn attaches_lists_and_removes_files() {
+        let vault = tempfile::tempdir().unwrap();
+        let source = vault.path().join("scan.png");
+        fs::write(&source, b"png").unwrap();
+        let mut db = default_db();
+        db.tasks = vec![sample_task("t1", "Card", "Backlog")];
+
+        assert!(attach_file_inner(&mut db, vault.path(), "t1", &source, "..").is_err());
+        assert!(attach_file_inner(&mut db, vault.path(), "nope", &source, "a.png").is_err());
+        let first =
+            attach_file_inner(&mut db, vault.path(), "t1", &source, "../../evil.png").unwrap();
+        assert_eq!(first.attachments, vec!["evil.png"]);
+        let second = attach_file_inner(&mut db, vault.path(), "t1", &source, "evil.png").unwrap();
+        assert_eq!(second.attachments, vec!["evil.png", "evil-2.png"]);
+        let dir = vault.path().join("attachments").join("t1");
+        assert_eq!(fs::read(dir.join("evil-2.png")).unwrap(), b"png");
+
+        let listed = list_attachments_inner(&db, vault.path(), "t1").unwrap();
+        assert!(listed.iter().all(|a| a.exists));
+
+        assert!(remove_attachment_inner(&mut db, vault.path(), "t1", "missing.png").is_err());
+        remove_attachment_inner(&mut db, vault.path(), "t1", "evil.png").unwrap();
+        assert!(!dir.join("evil.png").exists());
+        let task = remove_attachment_inner(&mut db, vault.path(), "t1", "evil-2.png").unwrap();
+        assert!(task.attachments.is_empty());
+        assert!(!dir.exists());
+
+        // A hand-edited entry pointing outside the task's folder is refused, not deleted.
+        fs::write(vault.path().join("keep.txt"), b"keep").unwrap();
+        db.tasks[0].attachments = vec!["../../keep.txt".to_string()];
+        assert!(remove_attachment_inner(&mut db, vault.path(), "t1", "../../keep.txt").is_err());
+        assert!(vault.path().join("keep.txt").exists());
+        assert_eq!(db.tasks[0].attachments.len(), 1);
+    }
+
+    #[test]
+    fn undoes_the_last_journaled_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+        let mut db = load_db(&path).unwrap();
+        assert!(undo_last_change_inner(&path).is_err());
+
+        let created = create_story_inner(&mut db, story_payload("Undo me"), false).unwrap();
+        save_db(&path, &db).unwrap();
+        label_change(&path, "create", "task", &created.id);
+        update_task_column_inner(&mut db, &created.id, "Review", false).unwrap();
+        save_db(&path, &db).unwrap();
+        label_change(&path, "move", "task", &created.id);
+        label_change(&path, "ignored", "task", "other");
+        // A save whose command logs nothing stays unlabelled, even if a later command logs.
+        add_checklist_item_inner(&mut db, &created.id, "step").unwrap();
+        save_db(&path, &db).unwrap();
+        drop(lock_db_file(&path).unwrap());
+        label_change(&path, "save", "column_template", "sprint");
+        let unlabelled = undo_last_change_inner(&path).unwrap();
+        assert_eq!(unlabelled.action, None);
+
+        let undone = undo_last_change_inner(&path).unwrap();
+        assert_eq!(undone.action.as_deref(), Some("move"));
+        assert_eq!(undone.id.as_deref(), Some(created.id.as_str()));
+        let db = load_db(&path).unwrap();
+        assert_eq!(get_task_inner(&db, &created.id).unwrap().column, "Inbox");
+
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "\n").unwrap();
+        assert!(undo_last_change_inner(&path).is_err());
+        assert!(undo_last_change_inner(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("nothing to undo"));
+    }
+
+    #[test]
+    fn attachment_changes_end_the_undo_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pm-db.json");
+        let mut db = load_db(&path).unwrap();
+        db.tasks = vec![sample_task("t1", "Files", "Backlog")];
+        save_db(&path, &db).unwrap();
+        let source = dir.path().join("notes.txt");
+        fs::write(&source, "hello").unwrap();
+
+        attach_file_inner(&mut db, dir.path(), "t1", &source, "notes.txt").unwrap();
+        save_db(&path, &db).unwrap();
+        forget_undo_history(&path);
+        assert!(undo_last_change_inner(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("nothing to undo"));
+
+        // Later edits can still be undone, back to the attachment but not past it.
+        add_checklist_item_inner(&mut db, "t1", "step").unwrap();
+        save_db(&path, &db).unwrap();
+        undo_last_change_inner(&path).unwrap();
+        assert!(undo_last_change_inner(&path).is_err());
+        clear_db_cache();
+        let task = get_task_inner(&load_db(&path).unwrap(), "t1").unwrap();
+        assert_eq!(task.attachments, vec!["notes.txt"]);
+        assert!(dir.path().join("attachments/t1/notes.txt").is_file());
+    }
+
+    #[test]
+    fn gets_board_metadata_only() {
+        let db = default_db();
+        let board = get_board_inner(&db, "default").unwrap();
+        assert_eq!(board.columns.len(), 6);
+        assert_eq!(
+            get_board_inner(&db, "missing").unwrap_err().code(),
+            "board_not_found"
+        );
+    }
+
+    #[test]
+    fn deletes_epics_and_reattaches_stories() {
+        let mut db = default_db();
+        db.epics = vec![sample_epic("e1", Some("p1")), sample_epic("e2", Some("p2"))];
+        let mut live = sample_task("t1", "Live", "Backlog");
+        live.epic_id = Some("e1".to_string());
+        live.project_id = Some("p1".to_string());
+        let mut archived = sample_task("t2", "Archived", "Done");
+        archived.epic_id = Some("e1".to_string());
+        archived.project_id = Some("p1".to_string());
+        db.tasks = vec![live, sample_task("t3", "Other", "Backlog")];
+        db.archive = vec![archived];
+
+        assert!(delete_epic_inner(&mut db, "e1", Some("missing")).is_err());
+        assert!(delete_epic_inner(&mut db, "e1", Some("e1")).is_err());
+        assert_eq!(db.epics.len(), 2);
+
+        let summary = delete_epic_inner(&mut db, "e1", Some("e2")).unwrap();
+        assert_eq!(summary.tasks_updated, vec!["t1", "t2"]);
+        assert_eq!(db.tasks[0].epic_id.as_deref(), Some("e2"));
+        assert_eq!(db.archive[0].epic_id.as_deref(), Some("e2"));
+        assert_eq!(db.tasks[0].project_id.as_deref(), Some("p2"));
+        assert_eq!(db.archive[0].project_id.as_deref(), Some("p2"));
+
+        let summary = delete_epic_inner(&mut db, "e2", None).unwrap();
+        assert_eq!(summary.tasks_updated.len(), 2);
+        assert!(db.epics.is_empty());
+        assert!(db.tasks.iter().all(|t| t.epic_id.is_none()));
+        assert!(delete_epic_inner(&mut db, "e2", None).is_err());
+    }
+
+    #[test]
+    fn moves_task_to_an_exact_position() {
+        let mut db = default_db();
+        let mut a = sample_task("a", "A", "Ready");
+        a.order = Some(10.0);
+        let mut b = sample_task("b", "B", "Ready");
+        b.order = Some(20.0);
+        let c = sample_task("c", "C", "Ready");
+        let moving = sample_task("m", "Moving", "Backlog");
+        db.tasks = vec![c, b, a, moving];
+        let payload = |column: &str, index| MoveTaskWithPositionPayload {
+            task_id: "m".to_string(),
+            target_column: column.to_string(),
+            target_index: index,
+            force: None,
+        };
+
+        let ids = move_task_with_position_inner(&mut db, &payload("Ready", 1)).unwrap();
+        assert_eq!(ids, vec!["a", "m", "b", "c"]);
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let ready = board.columns.iter().find(|c| c.name == "Ready").unwrap();
+        let shown = ready
+            .tasks
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(shown, ids);
+        assert_eq!(get_task_inner(&db, "m").unwrap().transitions.len(), 1);
+
+        let ids = move_task_with_position_inner(&mut db, &payload("Ready", 99)).unwrap();
+        assert_eq!(ids, vec!["a", "b", "c", "m"]);
+        assert!(move_task_with_position_inner(&mut db, &payload("Nowhere", 0)).is_err());
+    }
+
+    #[test]
+    fn normalizes_suggested_tags() {
+        let existing = vec!["Backend".to_string()];
+        let content = "```json\n{\"tags\": [\"#Auth\", \"backend\", \"Login Flow\", \"auth\", \"\", 7, \"a\", \"b\", \"c\", \"d\"]}\n```";
+        let tags = parse_suggested_tags(content, &existing).unwrap();
+        assert_eq!(tags, vec!["auth", "login-flow", "a", "b", "c", "d"]);
+
+        let tags = parse_suggested_tags("{\"tags\": \"ui, Dark Mode\"}", &[]).unwrap();
+        assert_eq!(tags, vec!["ui", "dark-mode"]);
+        let err = parse_suggested_tags("{\"labels\": []}", &[]).unwrap_err();
+        assert_eq!(err.code(), "openai_response");
+    }
+
+    #[test]
+    fn mock_openai_answers_are_deterministic_and_parse() {
+        let payload: OpenAiAutoFillPayload = serde_json::from_value(json!({
+            "title": null,
+            "description": "Let admins export the audit log as CSV\nMore detail here.",
+            "asA": "admin",
+            "iWant": null,
+            "soThat": null,
+            "acceptanceCriteria": null
+        }))
+        .unwrap();
+        let content = mock_autofill_content(&payload);
+        assert_eq!(content, mock_autofill_content(&payload));
+        let parsed = parse_autofill_content(&content).unwrap();
+        assert_eq!(
+            parsed.title.as_deref(),
+            Some("Let admins export the audit log")
+        );
+        assert_eq!(parsed.as_a.as_deref(), Some("admin"));
+        assert_eq!(
+            parsed.i_want.as_deref(),
+            Some("Let admins export the audit log as CSV")
+        );
+        assert_eq!(parsed.acceptance_criteria.map(|c| c.len()), Some(1));
+
+        let tags = SuggestTagsPayload {
+            title: "Export audit log (CSV)".to_string(),
+            body: None,
+            existing_tags: vec!["audit".to_string()],
+        };
+        let suggested =
+            parse_suggested_tags(&mock_suggest_tags_content(&tags), &tags.existing_tags);
+        assert_eq!(suggested.unwrap(), vec!["export"]);
+    }
+
+    #[test]
+    fn saves_and_uses_column_templates() {
+        let vault = tempfile::tempdir().unwrap();
+        assert!(list_column_templates_inner(vault.path())
+            .unwrap()
+            .is_empty());
+        assert!(
+            save_column_template_inner(vault.path(), "../escape", vec!["A".to_string()]).is_err()
+        );
+        assert!(save_column_template_inner(vault.path(), "empty", vec![]).is_err());
+
+        let columns = vec![
+            "Todo".to_string(),
+            " Doing ".to_string(),
+            "Done".to_string(),
+        ];
+        save_column_template_inner(vault.path(), "sprint", columns).unwrap();
+        fs::write(
+            vault.path().join("templates/columns/broken.json"),
+            "not json",
+        )
+        .unwrap();
+        let templates = list_column_templates_inner(vault.path()).unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].columns, vec!["Todo", "Doing", "Done"]);
+
+        let mut db = default_db();
+        let template = load_column_template(vault.path(), "sprint").unwrap();
+        let board =
+            create_board_from_template_inner(&mut db, "Sprint 12", &template, true).unwrap();
+        assert_eq!(board.id, "sprint-12");
+        assert_eq!(
+            get_board_inner(&db, "sprint-12").unwrap().columns,
+            template.columns
+        );
+        let clash = create_board_from_template_inner(&mut db, "Default", &template, true).unwrap();
+        assert_eq!(clash.id, "default-2");
+        assert_eq!(get_board_inner(&db, "default").unwrap().columns.len(), 6);
+        assert!(load_column_template(vault.path(), "missing").is_err());
+        assert!(create_board_from_template_inner(&mut db, " ", &template, false).is_err());
+    }
+
+    #[test]
+    fn groups_tasks_due_in_range_by_date() {
+        let mut db = default_db();
+        let dues = [
+            ("a", Some("2026-03-02")),
+            ("b", Some("2026-03-02T09:30")),
+            ("c", Some("2026-03-08")),
+            ("d", Some("2026-03-09")),
+            ("e", Some("next tuesday")),
+            ("f", None),
+        ];
+        for (id, due) in dues {
+            let mut task = sample_task(id, id, "Todo");
+            task.due = due.map(str::to_string);
+            db.tasks.push(task);
+        }
+
+        let calendar = get_tasks_due_between_inner(&db, None, "2026-03-02", "2026-03-08").unwrap();
+        let days = calendar
+            .days
+            .iter()
+            .map(|d| (d.date.as_str(), d.tasks.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(days, vec![("2026-03-02", 2), ("2026-03-08", 1)]);
+        assert_eq!(calendar.unparseable, 1);
+
+        assert!(get_tasks_due_between_inner(&db, None, "2026-03-09", "2026-03-02").is_err());
+        assert!(get_tasks_due_between_inner(&db, None, "March", "2026-03-02").is_err());
+    }
+
+    #[test]
+    fn exports_board_as_csv_in_column_then_task_order() {
+        let mut db = default_db();
+        let mut second = sample_task("b", "Second, with comma", "Ready");
+        second.order = Some(2.0);
+        second.tags = vec!["ui".to_string(), "bug".to_string()];
+        let mut first = sample_task("a", "First\nline", "Ready");
+        first.order = Some(1.0);
+        first.due = Some("2026-03-02".to_string());
+        db.tasks.push(sample_task("c", "Shipped", "Done"));
+        db.tasks.push(second);
+        db.tasks.push(first);
+
+        let board = board_with_tasks_inner(&db, "default").unwrap();
+        let csv_text = render_board_csv(&board).unwrap();
+        let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["id", "title", "column", "tags", "due", "created", "updated"]
+        );
+        let rows = reader
+            .records()
+            .map(|r| r.unwrap().iter().map(str::to_string).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0][..5],
+            ["a", "First\nline", "Ready", "", "2026-03-02"]
+        );
+        assert_eq!(rows[1][..4], ["b", "Second, with comma", "Ready", "ui;bug"]);
+        assert_eq!(rows[2][0], "c");
+    }
+
+    #[test]
+    fn sets_validates_and_round_trips_task_color() {
+        let mut db = default_db();
+        db.tasks.push(sample_task("a", "Alpha", "Ready"));
+
+        assert_eq!(
+            set_task_color_inner(&mut db, "a", Some(" Blue "))
+                .unwrap()
+                .color
+                .as_deref(),
+            Some("blue")
+        );
+        assert_eq!(
+            set_task_color_inner(&mut db, "a", Some("#A1B2C3"))
+                .unwrap()
+                .color
+                .as_deref(),
+            Some("#a1b2c3")
+        );
+        for bad in ["magenta", "#abc", "#ggg000", "a1b2c3"] {
+            assert!(
+                set_task_color_inner(&mut db, "a", Some(bad)).is_err(),
+                "{bad}"
+            );
+        }
+        assert_eq!(db.tasks[0].color.as_deref(), Some("#a1b2c3"));
+
+        let raw = serde_json::to_string(&db).unwrap();
+        assert!(raw.contains(r##""color":"#a1b2c3""##));
+        let reloaded: Db = serde_json::from_str(&raw).unwrap();
+        assert_eq!(reloaded.tasks[0].color.as_deref(), Some("#a1b2c3"));
+
+        assert!(set_task_color_inner(&mut db, "a", None)
+            .unwrap()
+            .color
+            .is_none());
+        assert!(!serde_json::to_string(&db).unwrap().contains("color"));
+    }
+
+    #[test]
+    fn bulk_tags_tasks_and_reports_failures() {
+        let mut db = default_db();
+        let mut tagged = sample_task("a", "Alpha", "Ready");
+        tagged.tags = vec!["triage".to_string()];
+        db.tasks.push(tagged);
+        db.tasks.push(sample_task("b", "Beta", "Ready"));
+
+        let ids = ["a", "missing", "b"].map(str::to_string);
+        let results = bulk_tag_inner(&mut db, &ids, "triage", false).unwrap();
+        assert_eq!(
+            results.iter().map(|r| r.ok).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(results[1].error.as_ref().unwrap().code, "task_not_found");
+        assert!(db.tasks.iter().all(|t| t.tags == vec!["triage"]));
+        assert!(db.tasks.iter().all(|t| t.updated.is_some()));
+
+        assert!(bulk_tag_inner(&mut db, &ids, "  ", false).is_err());
+    }
+
+    #[test]
+    fn reports_vault_size_per_top_level_entry() {
+        let vault = tempfile::tempdir().unwrap();
+        let report = vault_size_report_inner(vault.path()).unwrap();
+        assert_eq!(report.total_bytes, 0);
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].name, "attachments");
+
+        fs::write(vault.path().join("pm-db.json"), "0123456789").unwrap();
+        let nested = vault.path().join("attachments").join("t1");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("a.png"), "abc").unwrap();
+        fs::write(nested.join("b.png"), "de").unwrap();
+
+        let report = vault_size_report_inner(vault.path()).unwrap();
+        assert_eq!(report.total_files, 3);
+        assert_eq!(report.total_bytes, 15);
+        assert_eq!(
+            report.entries,
+            vec![
+                VaultSizeEntry {
+                    name: "pm-db.json".to_string(),
+                    files: 1,
+                    bytes: 10,
+                },
+                VaultSizeEntry {
+                    name: "attachments".to_string(),
+                    files: 2,
+                    bytes: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn vault_change_ignores_access_and_other_files() {
+        use notify::event::{AccessKind, CreateKind, DataChange, ModifyKind};
+        let db_file = Path::new("/vault/pm-db.json");
+        let event = |kind, path: &str| Ok(notify::Event::new(kind).add_path(PathBuf::from(path)));
+
+        let modified = notify::EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        assert!(is_vault_change(
+            &event(modified, "/vault/pm-db.json"),
+            db_file
+        ));
+        assert!(is_vault_change(
+            &event(
+                notify::EventKind::Create(CreateKind::File),
+                "/vault/pm-db.json"
+            ),
+            db_file
+        ));
+        assert!(!is_vault_change(
+            &event(
+                notify::EventKind::Access(AccessKind::Read),
+                "/vault/pm-db.json"
+            ),
+            db_file
+        ));
+        assert!(!is_vault_change(
+            &event(modified, "/vault/activity.jsonl"),
+            db_file
+        ));
+        assert!(!is_vault_change(
+            &Err(notify::Error::generic("watch failed")),
+            db_file
+        ));
+    }
+}
@@ -18,6 +18,17 @@ type Task = {
   due?: string | null;
   created?: string | null;
   updated?: string | null;
+  projectId?: string | null;
+  epicId?: string | null;
+  asA?: string | null;
+  iWant?: string | null;
+  soThat?: string | null;
+  acceptanceCriteria?: string[] | null;
+  priority?: "low" | "medium" | "high" | "urgent" | null;
+  dueStatus?: {
+    kind: "overdue" | "dueToday" | "dueSoon" | "later" | "unparseable";
+    days?: number;
+  };
   body: string;
 };
 
@@ -60,6 +71,18 @@ type AutoFillResponse = {
   acceptanceCriteria?: string[] | null;
 };
 
+type ErrorResponse = {
+  code: string;
+  message: string;
+};
+
+function errorMessage(e: unknown): string {
+  if (e && typeof e === "object" && "message" in e) {
+    return (e as ErrorResponse).message;
+  }
+  return String(e);
+}
+
 export default function App() {
   const [vaultInfo, setVaultInfo] = useState<VaultInfo | null>(null);
   const [boards, setBoards] = useState<Board[]>([]);
@@ -106,7 +129,7 @@ export default function App() {
         setBoards(bs);
         setActiveBoardId((prev) => prev ?? (bs[0]?.id ?? null));
       } catch (e) {
-        setError(String(e));
+        setError(errorMessage(e));
       } finally {
         setLoading(false);
       }
@@ -123,7 +146,7 @@ export default function App() {
         });
         setBoardData(data);
       } catch (e) {
-        setError(String(e));
+        setError(errorMessage(e));
       }
     })();
   }, [activeBoardId]);
@@ -215,7 +238,6 @@ export default function App() {
           iWant: iWant || null,
           soThat: soThat || null,
           acceptanceCriteria: acceptanceCriteria.filter((c) => c.trim().length),
-          column: "Backlog",
         },
       });
       await refreshBoard();
@@ -256,7 +278,7 @@ export default function App() {
         setAcceptanceCriteria(result.acceptanceCriteria);
       }
     } catch (e) {
-      setAiError(String(e));
+      setAiError(errorMessage(e));
     } finally {
       setIsAutofilling(false);
     }